@@ -0,0 +1,173 @@
+use bevy::{asset::UntypedAssetId, prelude::*};
+
+// Images and font of the game and the atlas layouts of its sprite sheets.
+// They are loaded once, when the plugin is added, and shared by every sprite.
+#[derive(Resource)]
+pub struct GameAssets {
+    pub font: Handle<Font>,
+    pub background: Handle<Image>,
+    pub logo: Handle<Image>,
+    pub bob: Handle<Image>,
+    pub bob_layout: Handle<TextureAtlasLayout>,
+    pub platform: Handle<Image>,
+    pub platform_layout: Handle<TextureAtlasLayout>,
+    pub coin: Handle<Image>,
+    pub coin_layout: Handle<TextureAtlasLayout>,
+    pub squirrel: Handle<Image>,
+    pub squirrel_layout: Handle<TextureAtlasLayout>,
+    pub spring: Handle<Image>,
+    pub castle: Handle<Image>,
+    pub princess: Handle<Image>,
+    pub help: Vec<Handle<Image>>,
+    pub back: Handle<Image>,
+    pub pause: Handle<Image>,
+    pub sound_on: Handle<Image>,
+    pub sound_off: Handle<Image>,
+}
+
+pub const HELP_SCREEN_COUNT: usize = 5;
+
+impl FromWorld for GameAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mut layouts = world.resource_mut::<Assets<TextureAtlasLayout>>();
+        let mut layout = |tile_size: UVec2, columns: u32, rows: u32| {
+            layouts.add(TextureAtlasLayout::from_grid(
+                tile_size, columns, rows, None, None,
+            ))
+        };
+        let bob_layout = layout(UVec2::new(32, 32), 5, 1);
+        let platform_layout = layout(UVec2::new(64, 16), 1, 4);
+        let coin_layout = layout(UVec2::new(32, 32), 3, 1);
+        let squirrel_layout = layout(UVec2::new(32, 32), 2, 1);
+
+        let asset_server = world.resource::<AssetServer>();
+        Self {
+            font: asset_server.load("fonts/Retroville NC.ttf"),
+            background: asset_server.load("sprites/background.png"),
+            logo: asset_server.load("sprites/logo.png"),
+            bob: asset_server.load("sprites/bob.png"),
+            bob_layout,
+            platform: asset_server.load("sprites/platform.png"),
+            platform_layout,
+            coin: asset_server.load("sprites/coin.png"),
+            coin_layout,
+            squirrel: asset_server.load("sprites/squirrel.png"),
+            squirrel_layout,
+            spring: asset_server.load("sprites/spring.png"),
+            castle: asset_server.load("sprites/castle.png"),
+            princess: asset_server.load("sprites/princess.png"),
+            help: (1..=HELP_SCREEN_COUNT)
+                .map(|screen| asset_server.load(format!("sprites/help{}.png", screen)))
+                .collect(),
+            back: asset_server.load("sprites/back.png"),
+            pause: asset_server.load("sprites/pause.png"),
+            sound_on: asset_server.load("sprites/soundOn.png"),
+            sound_off: asset_server.load("sprites/soundOff.png"),
+        }
+    }
+}
+
+impl GameAssets {
+    fn atlas_sprite(image: &Handle<Image>, layout: &Handle<TextureAtlasLayout>) -> Sprite {
+        Sprite::from_atlas_image(
+            image.clone(),
+            TextureAtlas {
+                layout: layout.clone(),
+                index: 0,
+            },
+        )
+    }
+
+    pub fn bob_sprite(&self) -> Sprite {
+        Self::atlas_sprite(&self.bob, &self.bob_layout)
+    }
+
+    pub fn platform_sprite(&self) -> Sprite {
+        Self::atlas_sprite(&self.platform, &self.platform_layout)
+    }
+
+    pub fn coin_sprite(&self) -> Sprite {
+        Self::atlas_sprite(&self.coin, &self.coin_layout)
+    }
+
+    pub fn squirrel_sprite(&self) -> Sprite {
+        Self::atlas_sprite(&self.squirrel, &self.squirrel_layout)
+    }
+
+    pub fn sound_icon(&self, sound_enabled: bool) -> Handle<Image> {
+        if sound_enabled {
+            self.sound_on.clone()
+        } else {
+            self.sound_off.clone()
+        }
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = UntypedAssetId> {
+        [
+            &self.background,
+            &self.logo,
+            &self.bob,
+            &self.platform,
+            &self.coin,
+            &self.squirrel,
+            &self.spring,
+            &self.castle,
+            &self.princess,
+            &self.back,
+            &self.pause,
+            &self.sound_on,
+            &self.sound_off,
+        ]
+        .into_iter()
+        .chain(&self.help)
+        .map(|image| image.id().untyped())
+        .chain([self.font.id().untyped()])
+    }
+}
+
+#[derive(Resource)]
+pub struct AudioHandles {
+    pub click: Handle<AudioSource>,
+    pub coin: Handle<AudioSource>,
+    pub jump: Handle<AudioSource>,
+    pub highjump: Handle<AudioSource>,
+    pub hit: Handle<AudioSource>,
+    pub music: Handle<AudioSource>,
+}
+
+impl FromWorld for AudioHandles {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        Self {
+            click: asset_server.load("audio/click.ogg"),
+            coin: asset_server.load("audio/coin.ogg"),
+            jump: asset_server.load("audio/jump.ogg"),
+            highjump: asset_server.load("audio/highjump.ogg"),
+            hit: asset_server.load("audio/hit.ogg"),
+            music: asset_server.load("audio/music.ogg"),
+        }
+    }
+}
+
+impl AudioHandles {
+    pub fn ids(&self) -> impl Iterator<Item = UntypedAssetId> {
+        [
+            &self.click,
+            &self.coin,
+            &self.jump,
+            &self.highjump,
+            &self.hit,
+            &self.music,
+        ]
+        .into_iter()
+        .map(|audio| audio.id().untyped())
+    }
+}
+
+pub struct AssetsPlugin;
+impl Plugin for AssetsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameAssets>()
+            .init_resource::<AudioHandles>();
+    }
+}
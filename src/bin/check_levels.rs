@@ -0,0 +1,35 @@
+// Looks for a path to the castle in the levels generated from a range of
+// seeds.
+//
+//     cargo run --release --bin check_levels -- --seeds 10000 --start 0
+
+use bevy_superjumper::{
+    arg,
+    game::level::{check_level, generate_candidate_level},
+};
+use rand::{SeedableRng, rngs::StdRng};
+
+fn main() {
+    let start = number_arg("--start").unwrap_or(0);
+    let count = number_arg("--seeds").unwrap_or(10_000);
+
+    let mut failures = 0;
+    for seed in start..start + count {
+        // generate_level would retry, check its first attempt instead
+        let objects = generate_candidate_level(&mut StdRng::seed_from_u64(seed));
+        if let Err(no_path) = check_level(&objects) {
+            println!("seed {}: stuck at height {:.0}", seed, no_path.stuck_at);
+            failures += 1;
+        }
+    }
+
+    println!("No path found in {} of {} levels", failures, count);
+    if failures > 0 {
+        std::process::exit(1);
+    }
+}
+
+// Reads a `--name <number>` command line argument
+fn number_arg(name: &str) -> Option<u64> {
+    arg(name).and_then(|value| value.parse().ok())
+}
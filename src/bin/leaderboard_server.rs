@@ -0,0 +1,101 @@
+// Leaderboard server the game submits finished runs to, keeping the scores in
+// a local file.
+//
+//     cargo run --features server --bin leaderboard_server -- --port 8080 --file leaderboard.ron
+//
+// It only listens on this machine, unless given another address to listen
+// on, like `--host 0.0.0.0`.
+//
+// The game uses it once `leaderboard_url: Some("http://localhost:8080")` is
+// added to its settings file.
+
+use std::{
+    error::Error,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use bevy_superjumper::{
+    arg,
+    leaderboard::{LeaderboardEntry, LeaderboardStore, MAX_NAME_LENGTH, SCORES_PATH, STORED_COUNT},
+};
+use tiny_http::{Method, Request, Response, Server};
+
+const DEFAULT_HOST: &str = "127.0.0.1";
+const DEFAULT_PORT: &str = "8080";
+const DEFAULT_FILE: &str = "leaderboard.ron";
+// Largest submitted run accepted, far more than any entry needs
+const MAX_BODY_SIZE: u64 = 4096;
+
+fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
+    let host = arg("--host").unwrap_or_else(|| DEFAULT_HOST.to_string());
+    let port = arg("--port").unwrap_or_else(|| DEFAULT_PORT.to_string());
+    let path = PathBuf::from(arg("--file").unwrap_or_else(|| DEFAULT_FILE.to_string()));
+
+    let mut store = LeaderboardStore::read(&path).map_err(|err| err.to_string())?;
+    let server = Server::http(format!("{}:{}", host, port))?;
+    println!(
+        "Leaderboard listening on {}:{}, scores in {}",
+        host,
+        port,
+        path.display()
+    );
+
+    for request in server.incoming_requests() {
+        let response = handle_request(request, &mut store, &path);
+        if let Err(err) = response {
+            eprintln!("{}", err);
+        }
+    }
+    Ok(())
+}
+
+fn handle_request(
+    mut request: Request,
+    store: &mut LeaderboardStore,
+    path: &Path,
+) -> Result<(), Box<dyn Error>> {
+    let url = request.url().to_string();
+    let (route, query) = url.split_once('?').unwrap_or((&url, ""));
+    if route != SCORES_PATH {
+        return Ok(request.respond(Response::empty(404))?);
+    }
+
+    match request.method() {
+        Method::Get => {
+            let limit = query
+                .split('&')
+                .find_map(|param| param.strip_prefix("limit="))
+                .and_then(|limit| limit.parse().ok())
+                .unwrap_or(STORED_COUNT)
+                .min(STORED_COUNT);
+            let body = ron::to_string(store.top(limit))?;
+            Ok(request.respond(Response::from_string(body))?)
+        }
+        Method::Post => {
+            let mut body = String::new();
+            request
+                .as_reader()
+                .take(MAX_BODY_SIZE + 1)
+                .read_to_string(&mut body)?;
+            if body.len() as u64 > MAX_BODY_SIZE {
+                return Ok(request.respond(Response::empty(413))?);
+            }
+            let Ok(entry) = ron::from_str::<LeaderboardEntry>(&body) else {
+                return Ok(request.respond(Response::empty(400))?);
+            };
+            if entry.name.is_empty() || entry.name.chars().count() > MAX_NAME_LENGTH {
+                return Ok(request.respond(Response::empty(400))?);
+            }
+
+            println!(
+                "{} scored {} on seed {}",
+                entry.name, entry.score, entry.seed
+            );
+            store.insert(entry);
+            store.write(path)?;
+            Ok(request.respond(Response::empty(201))?)
+        }
+        _ => Ok(request.respond(Response::empty(405))?),
+    }
+}
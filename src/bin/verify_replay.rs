@@ -0,0 +1,76 @@
+// Re-simulates replays and checks that they score what they claim.
+//
+//     cargo run --release --bin verify_replay -- run1.replay run2.replay
+//
+// Without arguments, the game's last and best replays are checked. Replays of
+// hand-made levels are played on the level stored in them, which nothing
+// vouches for, so they are flagged as such.
+
+use std::fs;
+
+use bevy_superjumper::{
+    game::{Replay, verification::verify_replay},
+    settings::{ReplaySlot, read_replay},
+};
+
+fn main() {
+    let paths: Vec<String> = std::env::args().skip(1).collect();
+    let replays: Vec<(String, Option<Vec<u8>>)> = if paths.is_empty() {
+        [
+            (ReplaySlot::Last, "last run"),
+            (ReplaySlot::Best, "best run"),
+        ]
+        .into_iter()
+        .map(|(slot, name)| (name.to_string(), read_replay(slot)))
+        .collect()
+    } else {
+        paths
+            .into_iter()
+            .map(|path| {
+                let bytes = fs::read(&path).ok();
+                (path, bytes)
+            })
+            .collect()
+    };
+
+    let mut failures = 0;
+    for (name, bytes) in replays {
+        let Some(replay) = bytes.as_deref().and_then(Replay::from_bytes) else {
+            println!("{}: not a replay", name);
+            failures += 1;
+            continue;
+        };
+
+        match verify_replay(&replay) {
+            Ok(result) => println!(
+                "{}: {}, {} points, {:?}",
+                name,
+                level(&replay),
+                result.points,
+                result.outcome
+            ),
+            Err(rejected) => {
+                println!(
+                    "{}: rejected, claims {} points, {:?} but scores {} points, {:?}",
+                    name,
+                    rejected.claimed.points,
+                    rejected.claimed.outcome,
+                    rejected.simulated.points,
+                    rejected.simulated.outcome
+                );
+                failures += 1;
+            }
+        }
+    }
+
+    if failures > 0 {
+        std::process::exit(1);
+    }
+}
+
+fn level(replay: &Replay) -> String {
+    match &replay.level {
+        Some(level) => format!("hand-made level {} (as stored in the replay)", level.name),
+        None => format!("seed {}", replay.seed),
+    }
+}
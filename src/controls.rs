@@ -0,0 +1,274 @@
+#![allow(clippy::type_complexity)]
+
+use bevy::{input::InputSystems, prelude::*, ui::UiSystems};
+
+use crate::{
+    GameState,
+    assets::GameAssets,
+    cleanup, click_sound,
+    focus::BackButton,
+    input::{Action, Binding, Bindings, TouchSteering},
+    settings::{write_bindings, write_touch_steering},
+};
+
+#[derive(Component)]
+struct ControlsEntity;
+
+#[derive(Component)]
+struct BindingText(Action);
+
+#[derive(Component)]
+struct TouchSteeringText;
+
+#[derive(Component)]
+enum ControlsButtonAction {
+    Rebind(Action),
+    TouchSteering,
+    Reset,
+    Back,
+}
+
+// Action waiting for its new input
+#[derive(Resource, Default)]
+struct Rebinding(Option<Action>);
+
+const TRANSPARENT: Color = Color::linear_rgba(0.0, 0.0, 0.0, 0.0);
+
+pub struct ControlsPlugin;
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Rebinding>()
+            .add_systems(OnEnter(GameState::Controls), setup_controls)
+            .add_systems(
+                OnExit(GameState::Controls),
+                (click_sound, cleanup::<ControlsEntity>),
+            )
+            .add_systems(
+                PreUpdate,
+                // Before the UI and the focus see the input
+                capture_binding
+                    .after(InputSystems)
+                    .before(UiSystems::Focus)
+                    .run_if(in_state(GameState::Controls)),
+            )
+            .add_systems(
+                Update,
+                (
+                    ui_action,
+                    update_binding_texts
+                        .run_if(resource_changed::<Bindings>.or(resource_changed::<Rebinding>)),
+                    update_touch_steering_text.run_if(resource_changed::<TouchSteering>),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Controls)),
+            );
+    }
+}
+
+fn setup_controls(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    mut rebinding: ResMut<Rebinding>,
+    touch_steering: Res<TouchSteering>,
+) {
+    rebinding.0 = None;
+    let font = game_assets.font.clone();
+    let text = |text: String, font_size: f32| {
+        (
+            Text::new(text),
+            TextFont {
+                font: font.clone(),
+                font_size,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+            TextLayout::new_with_justify(Justify::Center),
+        )
+    };
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(10.0),
+                ..default()
+            },
+            ControlsEntity,
+        ))
+        .with_children(|parent| {
+            parent.spawn(text("CONTROLS".to_string(), 40.0));
+
+            for action in Action::ALL {
+                parent
+                    .spawn((
+                        Button,
+                        Node {
+                            width: Val::Percent(90.0),
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        BackgroundColor(TRANSPARENT),
+                        ControlsButtonAction::Rebind(action),
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(text(action.name().to_string(), 24.0));
+                        parent.spawn((text(String::new(), 12.0), BindingText(action)));
+                    });
+            }
+
+            parent
+                .spawn((
+                    Button,
+                    BackgroundColor(TRANSPARENT),
+                    ControlsButtonAction::TouchSteering,
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        text(touch_steering_text(*touch_steering), 24.0),
+                        TouchSteeringText,
+                    ));
+                });
+
+            parent
+                .spawn((
+                    Button,
+                    BackgroundColor(TRANSPARENT),
+                    ControlsButtonAction::Reset,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(text("RESET".to_string(), 24.0));
+                });
+
+            parent
+                .spawn((
+                    Button,
+                    Node {
+                        position_type: PositionType::Absolute,
+                        left: Val::Px(10.0),
+                        bottom: Val::Px(10.0),
+                        width: Val::Px(64.0),
+                        height: Val::Px(64.0),
+                        ..default()
+                    },
+                    BackgroundColor(TRANSPARENT),
+                    ControlsButtonAction::Back,
+                    BackButton,
+                ))
+                .with_children(|parent| {
+                    let icon = game_assets.back.clone();
+                    parent.spawn(ImageNode::new(icon));
+                });
+        });
+}
+
+fn ui_action(
+    interaction_query: Query<
+        (&Interaction, &ControlsButtonAction),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut game_state: ResMut<NextState<GameState>>,
+    mut rebinding: ResMut<Rebinding>,
+    mut bindings: ResMut<Bindings>,
+    mut touch_steering: ResMut<TouchSteering>,
+) {
+    for (interaction, action) in &interaction_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match action {
+            ControlsButtonAction::Rebind(action) => rebinding.0 = Some(*action),
+            ControlsButtonAction::TouchSteering => {
+                *touch_steering = touch_steering.next();
+                write_touch_steering(*touch_steering);
+            }
+            ControlsButtonAction::Reset => {
+                *bindings = Bindings::default();
+                write_bindings(&bindings);
+            }
+            ControlsButtonAction::Back => game_state.set(GameState::Menu),
+        }
+    }
+}
+
+// Binds the next key, button or touch to the action being rebound. The input
+// is consumed, so it doesn't also press a button.
+fn capture_binding(
+    mut rebinding: ResMut<Rebinding>,
+    mut bindings: ResMut<Bindings>,
+    mut keyboard_input: ResMut<ButtonInput<KeyCode>>,
+    mut mouse_button_input: ResMut<ButtonInput<MouseButton>>,
+    mut touch_input: ResMut<Touches>,
+    mut gamepads: Query<&mut Gamepad>,
+) {
+    let Some(action) = rebinding.0 else {
+        return;
+    };
+
+    let key = keyboard_input.get_just_pressed().next().copied();
+    let button = mouse_button_input.get_just_pressed().next().copied();
+    let touch = touch_input
+        .iter_just_pressed()
+        .next()
+        .map(|touch| touch.id());
+
+    let binding = if let Some(key) = key {
+        keyboard_input.clear_just_pressed(key);
+        Binding::Key(key)
+    } else if let Some(button) = button {
+        mouse_button_input.clear_just_pressed(button);
+        Binding::Mouse(button)
+    } else if let Some(id) = touch {
+        touch_input.clear_just_pressed(id);
+        Binding::Touch
+    } else if let Some((mut gamepad, button)) = gamepads.iter_mut().find_map(|gamepad| {
+        let button = gamepad.get_just_pressed().next().copied();
+        button.map(|button| (gamepad, button))
+    }) {
+        gamepad.digital_mut().clear_just_pressed(button);
+        Binding::Gamepad(button)
+    } else {
+        return;
+    };
+
+    bindings.rebind(action, binding);
+    write_bindings(&bindings);
+    rebinding.0 = None;
+}
+
+fn update_binding_texts(
+    mut query: Query<(&BindingText, &mut Text)>,
+    bindings: Res<Bindings>,
+    rebinding: Res<Rebinding>,
+) {
+    for (binding_text, mut text) in &mut query {
+        text.0 = if rebinding.0 == Some(binding_text.0) {
+            "PRESS ANY KEY OR BUTTON".to_string()
+        } else {
+            bindings
+                .get(binding_text.0)
+                .iter()
+                .map(|binding| binding.name())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+    }
+}
+
+fn touch_steering_text(touch_steering: TouchSteering) -> String {
+    format!("TOUCH: {}", touch_steering.name())
+}
+
+fn update_touch_steering_text(
+    mut query: Query<&mut Text, With<TouchSteeringText>>,
+    touch_steering: Res<TouchSteering>,
+) {
+    for mut text in &mut query {
+        text.0 = touch_steering_text(*touch_steering);
+    }
+}
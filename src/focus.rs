@@ -0,0 +1,214 @@
+#![allow(clippy::type_complexity, clippy::too_many_arguments)]
+
+use bevy::{prelude::*, ui::UiSystems};
+
+use crate::{
+    GameState,
+    game::PlayState,
+    input::{Action, Actions},
+};
+
+// How far the stick has to be pushed to move the focus
+const STICK_THRESHOLD: f32 = 0.5;
+const FOCUS_OUTLINE: Color = Color::srgb(1.0, 0.8, 0.2);
+
+// Button pressed to leave a screen, or undo the last step
+#[derive(Component)]
+pub struct BackButton;
+
+// Button that the keyboard and gamepad act on
+#[derive(Resource, Default)]
+pub struct Focus(Option<Entity>);
+
+impl Focus {
+    pub fn get(&self) -> Option<Entity> {
+        self.0
+    }
+}
+
+enum Navigation {
+    Towards(Vec2),
+    Next,
+    Previous,
+}
+
+// Lets every button be reached without a pointer. The arrow keys, the D-pad
+// or the left stick move the focus to the nearest button in that direction,
+// Tab to the next one. The confirm action presses the focused button, the
+// back action the back button of the screen. Pressing sets the button's
+// Interaction, so the screens handle it like a click.
+pub struct FocusPlugin;
+impl Plugin for FocusPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Focus>()
+            .add_systems(PreUpdate, update_focus.after(UiSystems::Focus))
+            .add_systems(Update, show_focus.run_if(resource_changed::<Focus>));
+    }
+}
+
+fn update_focus(
+    actions: Actions,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut focus: ResMut<Focus>,
+    mut buttons: Query<
+        (
+            Entity,
+            &UiGlobalTransform,
+            &InheritedVisibility,
+            &mut Interaction,
+            Has<BackButton>,
+        ),
+        With<Button>,
+    >,
+    game_state: Res<State<GameState>>,
+    play_state: Res<State<PlayState>>,
+    mut stick_pushed: Local<bool>,
+    mut pressed: Local<Option<Entity>>,
+) {
+    // Release the button pressed last frame, as a mouse click would
+    if let Some(entity) = pressed.take()
+        && let Ok((_, _, _, mut interaction, _)) = buttons.get_mut(entity)
+    {
+        interaction.set_if_neq(Interaction::None);
+    }
+
+    // The arrows and the stick steer Bob while running
+    if *play_state == PlayState::Running {
+        return;
+    }
+
+    // The arrows scroll the level in the editor
+    let arrows = *game_state != GameState::Editor;
+    let navigation = keyboard_navigation(&keyboard_input, arrows)
+        .or_else(|| gamepad_direction(&gamepads, &mut stick_pushed).map(Navigation::Towards));
+    // Clicks and touches press what they point at
+    let confirm = actions.just_pressed_without_pointer(Action::Confirm);
+    let back = actions.just_pressed_without_pointer(Action::Back);
+
+    let mut candidates: Vec<(Entity, Vec2)> = buttons
+        .iter()
+        .filter(|(_, _, visibility, _, _)| visibility.get())
+        .map(|(entity, transform, _, _, _)| (entity, transform.translation))
+        .collect();
+    // Reading order, top to bottom and left to right
+    candidates.sort_by(|(_, a), (_, b)| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+
+    let focused = focus.0.and_then(|entity| {
+        let index = candidates.iter().position(|(other, _)| *other == entity)?;
+        Some((index, candidates[index].1))
+    });
+    // Buttons can be hidden or despawned under the focus
+    if focused.is_none() && focus.0.is_some() {
+        focus.0 = None;
+    }
+    match (focused, navigation) {
+        // The first input only shows where the focus is. Confirm is left to
+        // the screens while nothing is focused.
+        (None, Some(_)) => focus.0 = candidates.first().map(|(entity, _)| *entity),
+        (Some((_, from)), Some(Navigation::Towards(direction))) => {
+            if let Some(entity) = nearest_button(&candidates, from, direction) {
+                focus.0 = Some(entity);
+            }
+        }
+        (Some((index, _)), Some(Navigation::Next)) => {
+            focus.0 = Some(candidates[(index + 1) % candidates.len()].0);
+        }
+        (Some((index, _)), Some(Navigation::Previous)) => {
+            focus.0 = Some(candidates[(index + candidates.len() - 1) % candidates.len()].0);
+        }
+        (Some(_), None) if confirm => *pressed = focus.0,
+        _ => {}
+    }
+
+    if back {
+        *pressed = buttons
+            .iter()
+            .find(|(_, _, visibility, _, is_back)| *is_back && visibility.get())
+            .map(|(entity, _, _, _, _)| entity);
+    }
+
+    if let Some(entity) = *pressed
+        && let Ok((_, _, _, mut interaction, _)) = buttons.get_mut(entity)
+    {
+        *interaction = Interaction::Pressed;
+    }
+}
+
+fn keyboard_navigation(keyboard_input: &ButtonInput<KeyCode>, arrows: bool) -> Option<Navigation> {
+    if keyboard_input.just_pressed(KeyCode::Tab) {
+        let shift = keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+        return Some(if shift {
+            Navigation::Previous
+        } else {
+            Navigation::Next
+        });
+    }
+
+    [
+        (KeyCode::ArrowUp, Vec2::NEG_Y),
+        (KeyCode::ArrowDown, Vec2::Y),
+        (KeyCode::ArrowLeft, Vec2::NEG_X),
+        (KeyCode::ArrowRight, Vec2::X),
+    ]
+    .into_iter()
+    .find(|(key, _)| arrows && keyboard_input.just_pressed(*key))
+    .map(|(_, direction)| Navigation::Towards(direction))
+}
+
+// Direction of the D-pad or stick, only when it was just pushed. Down is
+// positive, like in UI coordinates.
+fn gamepad_direction(gamepads: &Query<&Gamepad>, stick_pushed: &mut bool) -> Option<Vec2> {
+    for (button, direction) in [
+        (GamepadButton::DPadUp, Vec2::NEG_Y),
+        (GamepadButton::DPadDown, Vec2::Y),
+        (GamepadButton::DPadLeft, Vec2::NEG_X),
+        (GamepadButton::DPadRight, Vec2::X),
+    ] {
+        if gamepads.iter().any(|gamepad| gamepad.just_pressed(button)) {
+            return Some(direction);
+        }
+    }
+
+    let stick = gamepads
+        .iter()
+        .map(|gamepad| gamepad.left_stick() * Vec2::new(1.0, -1.0))
+        .find(|stick| stick.length() > STICK_THRESHOLD);
+    let was_pushed = std::mem::replace(stick_pushed, stick.is_some());
+    let stick = stick.filter(|_| !was_pushed)?;
+    Some(if stick.x.abs() > stick.y.abs() {
+        Vec2::new(stick.x.signum(), 0.0)
+    } else {
+        Vec2::new(0.0, stick.y.signum())
+    })
+}
+
+// The closest button in the direction, preferring ones in line with the
+// focused button
+fn nearest_button(candidates: &[(Entity, Vec2)], from: Vec2, direction: Vec2) -> Option<Entity> {
+    candidates
+        .iter()
+        .filter_map(|&(entity, position)| {
+            let offset = position - from;
+            let along = offset.dot(direction);
+            let across = (offset - direction * along).length();
+            (along > 1.0).then_some((entity, along + 2.0 * across))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(entity, _)| entity)
+}
+
+fn show_focus(
+    mut commands: Commands,
+    focus: Res<Focus>,
+    outlined: Query<Entity, (With<Button>, With<Outline>)>,
+) {
+    for entity in &outlined {
+        commands.entity(entity).remove::<Outline>();
+    }
+    if let Some(entity) = focus.0 {
+        commands
+            .entity(entity)
+            .try_insert(Outline::new(Val::Px(2.0), Val::Px(2.0), FOCUS_OUTLINE));
+    }
+}
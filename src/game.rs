@@ -1,361 +1,222 @@
-#![allow(clippy::type_complexity)]
-use rand::Rng;
-
+#![allow(clippy::type_complexity, clippy::too_many_arguments)]
 use crate::{
-    Background, GameState, SoundEvent, cleanup, click_sound,
-    help::has_user_input,
-    highscores::{HighScores, check_and_update_highscores},
-};
-use bevy::{
-    math::bounding::{Aabb2d, IntersectsVolume},
-    prelude::*,
+    Background, GameState,
+    assets::GameAssets,
+    click_sound,
+    highscores::{HighScoreEntry, HighScores, PendingHighScore, PlayerName, check_new_highscore},
+    input::confirm_just_pressed,
+    leaderboard::Leaderboard,
 };
+use bevy::{input::InputSystems, prelude::*};
 
 use bob::Bob;
-use castle::Castle;
-use coin::Coin;
-use platform::Platform;
-use spring::Spring;
-use squirrel::Squirrel;
 
-use self::level::GameObject;
+use self::{
+    ghost::{GhostPlayback, GhostRecording},
+    physics::CameraHeight,
+    simulation::{GameDynamicEntity, GameEntity, MovingObject},
+};
+
+pub use replay::{ActiveReplay, FinishedReplay, MoveInput, Replay};
+pub use simulation::{
+    Coins, GameObjects, LevelOverride, LevelSeed, PlayState, Playtest, Points, Practice,
+    RunFinished, RunLevel, RunOutcome, SeedOverride, SimulationPlugin, SimulationSet,
+};
 
 mod anim;
-mod bob;
-mod castle;
-mod coin;
+pub mod bob;
+pub mod castle;
+pub mod coin;
+pub mod collision;
+mod editor;
 mod game_ui;
-mod level;
-mod platform;
-mod spring;
-mod squirrel;
-
-#[derive(Component)]
-struct GameEntity;
-
-#[derive(Component)]
-struct GameDynamicEntity;
-
-#[derive(Component)]
-struct MovingObject {
-    width: f32,
-    velocity_x: f32,
-    dir: f32,
-}
-
-#[derive(Resource, Default)]
-pub struct Points(u32);
-
-#[derive(Resource, Default)]
-pub struct GameObjects(Vec<GameObject>);
-
-#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
-enum PlayState {
-    #[default]
-    Ready,
-    Running,
-    Paused,
-    GameOver,
-}
-
+mod ghost;
+pub mod level;
+pub mod physics;
+pub mod platform;
+mod replay;
+pub mod simulation;
+pub mod spring;
+pub mod squirrel;
+pub mod verification;
+
+// Sprites, UI, sounds and saved runs on top of the game simulation
 pub struct GamePlugin;
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
-        app.init_state::<PlayState>()
-            .init_resource::<Points>()
-            .init_resource::<GameObjects>()
-            .add_systems(OnEnter(GameState::Playing), (setup_play, game_ui::setup_ui))
-            .add_systems(
-                OnExit(GameState::Playing),
-                (click_sound, cleanup::<GameEntity>, reset_play),
-            )
-            .add_systems(
-                Update,
+        app.add_plugins((
+            SimulationPlugin,
+            level::LevelFilePlugin,
+            editor::EditorPlugin,
+        ))
+        .init_resource::<GhostRecording>()
+        .init_resource::<GhostPlayback>()
+        .add_systems(
+            OnEnter(GameState::Playing),
+            (
+                (setup_sprites, place_camera).after(simulation::setup_play),
+                game_ui::setup_ui,
+            ),
+        )
+        .add_systems(
+            OnExit(GameState::Playing),
+            (
+                click_sound,
+                (ghost::save_ghost, ghost::reset_ghost)
+                    .chain()
+                    .before(simulation::reset_simulation),
+                replay::save_replay
+                    .after(replay::record_replay)
+                    .before(replay::reset_replay),
+                reset_camera,
+            ),
+        )
+        .add_systems(
+            PreUpdate,
+            replay::read_move_input
+                .after(InputSystems)
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(
+            Update,
+            (
+                game_ui::ui_action,
+                game_ui::toggle_pause,
+                game_ui::update_buttons_visibility.run_if(state_changed::<PlayState>),
+                click_sound.run_if(state_changed::<PlayState>),
+                update_highscores,
                 (
-                    game_ui::ui_action,
-                    game_ui::update_buttons_visibility.run_if(state_changed::<PlayState>),
-                    click_sound.run_if(state_changed::<PlayState>),
-                    spawn_objects,
+                    (
+                        platform::add_platform_sprites,
+                        squirrel::add_squirrel_sprites,
+                        coin::add_coin_sprites,
+                        spring::add_spring_sprites,
+                        castle::add_castle_sprites,
+                    ),
+                    physics::interpolate_transforms,
+                    bob::camera_follow_bob,
                 )
-                    .run_if(in_state(GameState::Playing)),
+                    .chain(),
             )
-            .add_systems(
-                Update,
-                (
-                    coin_sound
-                        .run_if(resource_changed::<Points>.and(not(resource_added::<Points>))),
-                    game_ui::update_score_text.run_if(resource_changed::<Points>),
-                    bob::animate_bob,
-                    bob::update_bob,
-                    bob::move_bob,
-                    bob::check_bob_has_fallen,
-                    coin::animate_coins,
-                    squirrel::animate_squirrels,
-                    platform::animate_platforms,
-                    move_objects,
-                    check_platform_collisions,
-                    check_coin_collisions,
-                    check_squirrel_collisions,
-                    check_spring_collisions,
-                    check_castle_collisions,
-                    cleanup_objects,
-                )
-                    .run_if(in_state(GameState::Playing).and(in_state(PlayState::Running))),
-            )
-            .add_systems(
-                Update,
-                (
-                    game_ui::go_back_to_menu.run_if(has_user_input),
-                    bob::update_bob,
-                )
-                    .run_if(in_state(GameState::Playing).and(in_state(PlayState::GameOver))),
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(
+            Update,
+            (
+                game_ui::update_score_text.run_if(resource_changed::<Points>),
+                bob::animate_bob,
+                coin::animate_coins,
+                squirrel::animate_squirrels,
+                platform::animate_platforms,
             )
-            .add_systems(
-                OnEnter(PlayState::GameOver),
-                (bob::animate_bob_death, game_ui::spawn_game_over_ui),
-            );
+                .run_if(in_state(GameState::Playing).and(in_state(PlayState::Running))),
+        )
+        .add_systems(
+            FixedUpdate,
+            (ghost::record_ghost_frame, ghost::move_ghost).in_set(SimulationSet::Record),
+        )
+        .add_systems(
+            Update,
+            game_ui::go_back_to_menu.run_if(
+                in_state(GameState::Playing)
+                    .and(in_state(PlayState::GameOver))
+                    .and(confirm_just_pressed),
+            ),
+        )
+        .add_systems(
+            OnEnter(PlayState::GameOver),
+            (
+                bob::animate_bob_death,
+                game_ui::spawn_game_over_ui,
+                replay::save_replay.after(replay::record_replay),
+                ghost::save_ghost.after(simulation::finish_run),
+            ),
+        );
     }
 }
 
-fn setup_play(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    mut texture_atlases: ResMut<Assets<TextureAtlasLayout>>,
-    mut game_objects: ResMut<GameObjects>,
-) {
-    game_objects.0 = level::generate_level();
-
-    bob::setup_bob(&mut commands, &asset_server, &mut texture_atlases);
-}
-
-fn spawn_objects(
+fn setup_sprites(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    mut texture_atlases: ResMut<Assets<TextureAtlasLayout>>,
-    mut game_objects: ResMut<GameObjects>,
-    camera_query: Query<&Transform, With<Camera>>,
-) {
-    let max_y = camera_query.single().unwrap().translation.y + 1.1 * 480.0;
-
-    for object in &mut game_objects.0 {
-        // Only spawn objects that are on screen and a 10% above
-        if object.is_spawned || object.y > max_y {
-            continue;
-        }
-
-        object.is_spawned = true;
-
-        match object.object_type {
-            level::GameObjectType::Platform(moving) => {
-                platform::spawn_platform(
-                    &mut commands,
-                    &asset_server,
-                    &mut texture_atlases,
-                    moving,
-                    Vec2::new(object.x - 160.0, object.y - 240.0),
-                );
-            }
-            level::GameObjectType::Squirrel => {
-                squirrel::spawn_squirrel(
-                    &mut commands,
-                    &asset_server,
-                    &mut texture_atlases,
-                    Vec2::new(object.x - 160.0, object.y - 240.0),
-                );
-            }
-            level::GameObjectType::Coin => {
-                coin::spawn_coin(
-                    &mut commands,
-                    &asset_server,
-                    &mut texture_atlases,
-                    Vec2::new(object.x - 160.0, object.y - 240.0),
-                );
-            }
-            level::GameObjectType::Spring => {
-                spring::spawn_spring(
-                    &mut commands,
-                    &asset_server,
-                    Vec2::new(object.x - 160.0, object.y - 240.0),
-                );
-            }
-            level::GameObjectType::Castle => {
-                castle::spawn_castle(
-                    &mut commands,
-                    &asset_server,
-                    Vec2::new(object.x - 160.0, object.y - 240.0),
-                );
-            }
-        }
-    }
-}
-
-fn cleanup_objects(
-    mut commands: Commands,
-    mut game_objects: ResMut<GameObjects>,
-    mut dynamic_objects: Query<(Entity, &Transform), With<GameDynamicEntity>>,
-    camera_query: Query<&Transform, With<Camera>>,
-) {
-    let min_y = camera_query.single().unwrap().translation.y - 1.2 * 240.0;
-
-    game_objects.0.retain(|o| !o.is_spawned);
-
-    for (entity, transform) in &mut dynamic_objects {
-        // Despawn objects that are below screen's bottom
-        if transform.translation.y < min_y {
-            commands.entity(entity).despawn();
-        }
-    }
-}
-
-fn check_platform_collisions(
-    mut bob_query: Query<(&Transform, &mut Bob), With<Bob>>,
-    mut platforms_query: Query<(&Transform, &mut Platform), With<Platform>>,
-    mut sound_events: MessageWriter<SoundEvent>,
-    time: Res<Time>,
+    game_assets: Res<GameAssets>,
+    bob_query: Query<Entity, With<Bob>>,
+    level_seed: Res<LevelSeed>,
+    run_level: Res<RunLevel>,
+    practice: Res<Practice>,
+    mut ghost_playback: ResMut<GhostPlayback>,
 ) {
-    let (&bob_transform, mut bob) = bob_query.single_mut().unwrap();
-    if bob.velocity.y > 0.0 {
+    let bob = bob_query.single().unwrap();
+    let bob_sprite = bob::add_bob_sprite(&mut commands, bob, &game_assets);
+    // A playtest's level is still being edited, and has no best run yet
+    if practice.0 {
         return;
     }
-
-    for (&platform_transform, mut platform) in &mut platforms_query {
-        let collision = Aabb2d::new(bob_transform.translation.truncate(), bob::BOB_SIZE / 2.)
-            .intersects(&Aabb2d::new(
-                platform_transform.translation.truncate(),
-                platform::PLATFORM_SIZE / 2.,
-            ));
-
-        if collision {
-            bob.velocity.y = bob::BOB_JUMP_VELOCITY;
-
-            sound_events.write(SoundEvent::Jump);
-
-            let mut rng = rand::thread_rng();
-            if rng.gen_range(0.0..1.0) > 0.5 {
-                platform.state = platform::PlatformState::Pulverizing(time.elapsed_secs());
-            }
-            return;
-        }
-    }
+    ghost::spawn_ghost(
+        &mut commands,
+        &mut ghost_playback,
+        &bob_sprite,
+        &ghost::ghost_name(&level_seed, &run_level),
+    );
 }
 
-fn check_spring_collisions(
-    mut bob_query: Query<(&Transform, &mut Bob), With<Bob>>,
-    springs_query: Query<&Transform, With<Spring>>,
-    mut sound_events: MessageWriter<SoundEvent>,
+fn update_highscores(
+    mut run_finished: MessageReader<RunFinished>,
+    high_scores: Res<HighScores>,
+    mut pending: ResMut<PendingHighScore>,
+    leaderboard: Res<Leaderboard>,
+    player_name: Res<PlayerName>,
+    active_replay: Res<ActiveReplay>,
+    coins: Res<Coins>,
+    camera_height: Res<CameraHeight>,
+    level_seed: Res<LevelSeed>,
+    run_level: Res<RunLevel>,
+    practice: Res<Practice>,
 ) {
-    let (&bob_transform, mut bob) = bob_query.single_mut().unwrap();
-
-    if bob.velocity.y > 0.0 {
-        return;
-    }
-
-    for &spring_transform in &springs_query {
-        let collision = Aabb2d::new(bob_transform.translation.truncate(), bob::BOB_SIZE / 2.)
-            .intersects(&Aabb2d::new(
-                spring_transform.translation.truncate(),
-                spring::SPRING_SIZE / 2.,
-            ));
-
-        if collision {
-            bob.velocity.y = bob::BOB_JUMP_VELOCITY * 1.5;
-            sound_events.write(SoundEvent::Highjump);
-            return;
-        }
-    }
-}
-
-fn check_coin_collisions(
-    bob_query: Query<&Transform, With<Bob>>,
-    mut coins_query: Query<(Entity, &Transform), With<Coin>>,
-    mut points: ResMut<Points>,
-    mut commands: Commands,
-) {
-    let bob_transform = bob_query.single().unwrap();
-    for (entity, &coin_transform) in &mut coins_query {
-        let collision =
-            Aabb2d::new(bob_transform.translation.truncate(), bob::BOB_SIZE / 2.).intersects(
-                &Aabb2d::new(coin_transform.translation.truncate(), coin::COIN_SIZE / 2.),
+    for run in run_finished.read() {
+        if !active_replay.is_playing() && !practice.0 {
+            check_new_highscore(
+                &high_scores,
+                &mut pending,
+                &leaderboard,
+                &player_name,
+                run_entry(run.points, &coins, &camera_height, &level_seed, &run_level),
             );
-
-        if collision {
-            points.0 += coin::COIN_SCORE;
-            commands.entity(entity).despawn();
         }
     }
 }
 
-fn check_squirrel_collisions(
-    bob_query: Query<&Transform, With<Bob>>,
-    mut squirrels_query: Query<&Transform, With<Squirrel>>,
-    mut sound_events: MessageWriter<SoundEvent>,
-    mut play_state: ResMut<NextState<PlayState>>,
-) {
-    let bob_transform = bob_query.single().unwrap();
-    for &squirrel_transform in &mut squirrels_query {
-        let collision = Aabb2d::new(bob_transform.translation.truncate(), bob::BOB_SIZE / 2.)
-            .intersects(&Aabb2d::new(
-                squirrel_transform.translation.truncate(),
-                squirrel::SQUIRREL_SIZE / 2.,
-            ));
-
-        if collision {
-            sound_events.write(SoundEvent::Hit);
-            play_state.set(PlayState::GameOver);
-            return;
-        }
-    }
+// High score entry of the current run
+fn run_entry(
+    points: u32,
+    coins: &Coins,
+    camera_height: &CameraHeight,
+    level_seed: &LevelSeed,
+    run_level: &RunLevel,
+) -> HighScoreEntry {
+    // The camera follows Bob's highest point, from the middle of the screen
+    HighScoreEntry::new(
+        points,
+        camera_height.0 + 240.0,
+        coins.0,
+        level_seed.0,
+        run_level.0.as_ref().map(|level| level.name.clone()),
+    )
 }
 
-fn check_castle_collisions(
-    bob_query: Query<&Transform, With<Bob>>,
-    castles_query: Query<&Transform, With<Castle>>,
-    mut game_state: ResMut<NextState<GameState>>,
-    points: Res<Points>,
-    mut high_scores: ResMut<HighScores>,
-    mut play_state: ResMut<NextState<PlayState>>,
-) {
-    let bob_transform = bob_query.single().unwrap();
-    for castle_transform in &castles_query {
-        let collision = Aabb2d::new(bob_transform.translation.truncate(), bob::BOB_SIZE / 2.)
-            .intersects(&Aabb2d::new(
-                castle_transform.translation.truncate(),
-                castle::CASTLE_SIZE / 2.,
-            ));
-
-        if collision {
-            check_and_update_highscores(&mut high_scores, points.0);
-            game_state.set(GameState::WinScreen);
-            play_state.set(PlayState::Ready);
-        }
-    }
-}
-
-fn move_objects(
-    mut objects_query: Query<(&mut MovingObject, &mut Transform), With<MovingObject>>,
-    time: Res<Time>,
+// Runs start with the camera at the bottom of the level, unless playtesting
+// from the editor
+fn place_camera(
+    camera_height: Res<CameraHeight>,
+    mut camera_query: Query<&mut Transform, (With<Camera>, Without<Background>)>,
+    mut bg_query: Query<&mut Transform, (With<Background>, Without<Camera>)>,
 ) {
-    for (mut obj, mut transform) in &mut objects_query {
-        transform.translation.x += obj.velocity_x * obj.dir * time.delta_secs();
-
-        if transform.translation.x + obj.width / 2.0 > 160.0 {
-            obj.dir = -1.0;
-        } else if transform.translation.x - obj.width / 2.0 < -160.0 {
-            obj.dir = 1.0;
-        }
-    }
-}
-
-fn coin_sound(mut sound_events: MessageWriter<SoundEvent>) {
-    sound_events.write(SoundEvent::Coin);
+    camera_query.single_mut().unwrap().translation.y = camera_height.0;
+    bg_query.single_mut().unwrap().translation.y = camera_height.0;
 }
 
-fn reset_play(
+fn reset_camera(
     mut camera_query: Query<&mut Transform, (With<Camera>, Without<Background>)>,
     mut bg_query: Query<&mut Transform, (With<Background>, Without<Camera>)>,
-    mut points: ResMut<Points>,
 ) {
     camera_query.single_mut().unwrap().translation.y = 0.0;
     bg_query.single_mut().unwrap().translation.y = 0.0;
-    points.0 = 0;
 }
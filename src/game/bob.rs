@@ -1,14 +1,19 @@
-use super::{GameEntity, PlayState};
+use super::{
+    GameEntity, PlayState,
+    physics::{CameraHeight, Position, PreviousPosition},
+    replay::MoveInput,
+};
 use crate::{
     Background,
+    assets::GameAssets,
     game::anim::{AnimationIndices, AnimationTimer},
 };
 use bevy::prelude::*;
 
 //const BOB_ANIMATION_SPEED: f32 = 10.0;
 pub const BOB_JUMP_VELOCITY: f32 = 400.0; // 11
-const BOB_MOVE_VELOCITY: f32 = 500.0; // 20
-const ACCELERATION_X: f32 = 0.5;
+pub const BOB_MOVE_VELOCITY: f32 = 500.0; // 20
+pub const ACCELERATION_X: f32 = 0.5;
 pub const GRAVITY_Y: f32 = -480.0; // -12
 pub const BOB_SIZE: Vec2 = Vec2::new(32.0, 32.0);
 
@@ -17,42 +22,38 @@ pub struct Bob {
     pub velocity: Vec2,
 }
 
-pub(super) fn setup_bob(
-    commands: &mut Commands,
-    asset_server: &Res<AssetServer>,
-    texture_atlases: &mut ResMut<Assets<TextureAtlasLayout>>,
-) {
-    // Load the bob's sprite sheet and create a texture atlas from it
-    let bob_texture = asset_server.load("sprites/bob.png");
-    let layout_handle = texture_atlases.add(TextureAtlasLayout::from_grid(
-        UVec2::new(32, 32),
-        5,
-        1,
-        None,
-        None,
+pub(super) fn setup_bob(commands: &mut Commands, height: f32) {
+    // Spawn bob
+    let position = Vec2::new(0.0, height);
+    commands.spawn((
+        Bob::default(),
+        Position(position),
+        PreviousPosition(position),
+        GameEntity,
+        Transform::from_xyz(position.x, position.y, 20.0),
     ));
+}
 
+pub(super) fn add_bob_sprite(
+    commands: &mut Commands,
+    bob: Entity,
+    game_assets: &GameAssets,
+) -> Sprite {
     let animation_indices = AnimationIndices {
         first: 0,
         last: 3,
         death: 4,
     };
 
-    // Spawn bob
-    commands.spawn((
-        Bob::default(),
-        GameEntity,
-        Sprite::from_atlas_image(
-            bob_texture,
-            TextureAtlas {
-                layout: layout_handle,
-                index: animation_indices.first,
-            },
-        ),
-        Transform::from_xyz(0.0, -240.0 + 32.0, 20.0),
+    let sprite = game_assets.bob_sprite();
+
+    commands.entity(bob).insert((
+        sprite.clone(),
         animation_indices,
         AnimationTimer(Timer::from_seconds(0.1, TimerMode::Repeating)),
     ));
+
+    sprite
 }
 
 pub(super) fn animate_bob(
@@ -90,14 +91,12 @@ pub(super) fn animate_bob(
 }
 
 pub(super) fn update_bob(
-    mut bob_query: Query<(&mut Transform, &mut Bob), With<Bob>>,
-    mut camera_query: Query<&mut Transform, (With<Camera>, Without<Bob>, Without<Background>)>,
-    mut bg_query: Query<&mut Transform, (With<Background>, Without<Bob>, Without<Camera>)>,
+    mut bob_query: Query<(&mut Position, &mut Bob), With<Bob>>,
+    mut camera_height: ResMut<CameraHeight>,
     play_state: Res<State<PlayState>>,
     time: Res<Time>,
 ) {
-    let (mut transform, mut bob) = bob_query.single_mut().unwrap();
-    let mut camera = camera_query.single_mut().unwrap();
+    let (mut position, mut bob) = bob_query.single_mut().unwrap();
 
     bob.velocity.y += GRAVITY_Y * time.delta_secs();
 
@@ -105,45 +104,50 @@ pub(super) fn update_bob(
         if bob.velocity.y >= 0.0 {
             bob.velocity.y = 0.0;
         }
-        if transform.translation.y > camera.translation.y - 240.0 {
-            transform.translation.y += bob.velocity.y * time.delta_secs();
+        if position.y > camera_height.0 - 240.0 {
+            position.y += bob.velocity.y * time.delta_secs();
         }
     } else {
-        transform.translation.x += bob.velocity.x * time.delta_secs();
-        transform.translation.y += bob.velocity.y * time.delta_secs();
+        position.x += bob.velocity.x * time.delta_secs();
+        position.y += bob.velocity.y * time.delta_secs();
 
-        if transform.translation.y < -240.0 + 16.0 {
+        if position.y < -240.0 + 16.0 {
             bob.velocity.y = BOB_JUMP_VELOCITY;
         }
 
-        if transform.translation.x < -160.0 {
-            transform.translation.x += 320.0;
+        if position.x < -160.0 {
+            position.x += 320.0;
         }
-        if transform.translation.x > 160.0 {
-            transform.translation.x -= 320.0;
+        if position.x > 160.0 {
+            position.x -= 320.0;
         }
 
-        if transform.translation.y > camera.translation.y {
-            camera.translation.y = transform.translation.y;
-            bg_query.single_mut().unwrap().translation.y = transform.translation.y;
+        if position.y > camera_height.0 {
+            camera_height.0 = position.y;
         }
     }
 }
 
-pub(super) fn move_bob(
-    mut bob: Query<&mut Bob, With<Bob>>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+pub(super) fn camera_follow_bob(
+    bob_query: Query<&Transform, With<Bob>>,
+    mut camera_query: Query<&mut Transform, (With<Camera>, Without<Bob>, Without<Background>)>,
+    mut bg_query: Query<&mut Transform, (With<Background>, Without<Bob>, Without<Camera>)>,
+    play_state: Res<State<PlayState>>,
 ) {
+    let Ok(bob_transform) = bob_query.single() else {
+        return;
+    };
+    let mut camera = camera_query.single_mut().unwrap();
+
+    if *play_state != PlayState::GameOver && bob_transform.translation.y > camera.translation.y {
+        camera.translation.y = bob_transform.translation.y;
+        bg_query.single_mut().unwrap().translation.y = bob_transform.translation.y;
+    }
+}
+
+pub(super) fn move_bob(mut bob: Query<&mut Bob, With<Bob>>, move_input: Res<MoveInput>) {
     for mut bob in &mut bob {
-        if keyboard_input.pressed(KeyCode::ArrowRight) || keyboard_input.pressed(KeyCode::KeyD) {
-            bob.velocity.x = ACCELERATION_X * BOB_MOVE_VELOCITY;
-        } else if keyboard_input.pressed(KeyCode::ArrowLeft)
-            || keyboard_input.pressed(KeyCode::KeyA)
-        {
-            bob.velocity.x = -ACCELERATION_X * BOB_MOVE_VELOCITY;
-        } else {
-            bob.velocity.x = 0.0;
-        }
+        bob.velocity.x = move_input.0 * ACCELERATION_X * BOB_MOVE_VELOCITY;
     }
 }
 
@@ -156,16 +160,13 @@ pub(super) fn animate_bob_death(mut bob_query: Query<(&AnimationIndices, &mut Sp
 }
 
 pub(super) fn check_bob_has_fallen(
-    bob_query: Query<&Transform, With<Bob>>,
-    camera_query: Query<&Transform, (With<Camera>, Without<Bob>)>,
+    bob_query: Query<&Position, With<Bob>>,
+    camera_height: Res<CameraHeight>,
     mut play_state: ResMut<NextState<PlayState>>,
 ) {
-    let bob_transform = bob_query.single();
-    let camera = camera_query.single().unwrap();
+    let bob_position = bob_query.single().unwrap();
 
-    if bob_transform.unwrap().translation.y <= camera.translation.y - 240.0
-        && camera.translation.y > 0.0
-    {
+    if bob_position.y <= camera_height.0 - 240.0 && camera_height.0 > 0.0 {
         play_state.set(PlayState::GameOver);
     }
 }
@@ -1,4 +1,9 @@
-use super::{GameDynamicEntity, GameEntity};
+use super::{
+    GameDynamicEntity, GameEntity, PlayState, Points, RunFinished, RunOutcome,
+    collision::{BobTouched, Collider, CollisionLayer},
+    physics::Position,
+};
+use crate::{GameState, assets::GameAssets};
 use bevy::prelude::*;
 
 pub const CASTLE_SIZE: Vec2 = Vec2::new(64.0, 64.0);
@@ -6,19 +11,48 @@ pub const CASTLE_SIZE: Vec2 = Vec2::new(64.0, 64.0);
 #[derive(Component, Default)]
 pub struct Castle;
 
-pub(super) fn spawn_castle(
-    commands: &mut Commands,
-    asset_server: &Res<AssetServer>,
-    position: Vec2,
-) {
-    let castle_texture = asset_server.load("sprites/castle.png");
-
-    // Spawn spring
+pub(super) fn spawn_castle(commands: &mut Commands, position: Vec2) {
+    // Spawn castle
     commands.spawn((
         Castle,
+        Collider::rect(CASTLE_SIZE, CollisionLayer::Touch),
         GameEntity,
         GameDynamicEntity,
-        Sprite::from_image(castle_texture),
         Transform::from_xyz(position.x, position.y, 20.0),
+        Position(position),
     ));
 }
+
+pub(super) fn enter_castle(
+    mut touched: MessageReader<BobTouched>,
+    castles_query: Query<(), With<Castle>>,
+    mut game_state: ResMut<NextState<GameState>>,
+    mut play_state: ResMut<NextState<PlayState>>,
+    mut run_finished: MessageWriter<RunFinished>,
+    points: Res<Points>,
+) {
+    for BobTouched(entity) in touched.read() {
+        if !castles_query.contains(*entity) {
+            continue;
+        }
+
+        run_finished.write(RunFinished {
+            outcome: RunOutcome::ReachedCastle,
+            points: points.0,
+        });
+        game_state.set(GameState::WinScreen);
+        play_state.set(PlayState::Ready);
+    }
+}
+
+pub(super) fn add_castle_sprites(
+    mut commands: Commands,
+    castle_query: Query<Entity, Added<Castle>>,
+    game_assets: Res<GameAssets>,
+) {
+    for entity in &castle_query {
+        commands
+            .entity(entity)
+            .insert(Sprite::from_image(game_assets.castle.clone()));
+    }
+}
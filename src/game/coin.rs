@@ -1,6 +1,14 @@
-use crate::game::anim::{AnimationIndices, AnimationTimer};
+use crate::{
+    Sound, SoundEvent,
+    assets::GameAssets,
+    game::anim::{AnimationIndices, AnimationTimer},
+};
 
-use super::{GameDynamicEntity, GameEntity};
+use super::{
+    Coins, GameDynamicEntity, GameEntity, Points,
+    collision::{BobTouched, Collider, CollisionLayer},
+    physics::Position,
+};
 use bevy::prelude::*;
 
 //const COIN_ANIMATION_SPEED: f32 = 10.0;
@@ -11,46 +19,58 @@ pub const COIN_SCORE: u32 = 10;
 #[derive(Component, Default)]
 pub struct Coin;
 
-pub(super) fn spawn_coin(
-    commands: &mut Commands,
-    asset_server: &Res<AssetServer>,
-    texture_atlases: &mut ResMut<Assets<TextureAtlasLayout>>,
-    position: Vec2,
-) {
-    // Load the coin's sprite sheet and create a texture atlas from it
-    let coin_texture = asset_server.load("sprites/coin.png");
-    let layout_handle = texture_atlases.add(TextureAtlasLayout::from_grid(
-        UVec2::new(32, 32),
-        3,
-        1,
-        None,
-        None,
-    ));
-
-    let animation_indices = AnimationIndices {
-        first: 0,
-        last: 2,
-        ..default()
-    };
-
+pub(super) fn spawn_coin(commands: &mut Commands, position: Vec2) {
     // Spawn coin
     commands.spawn((
         Coin,
+        Collider::rect(COIN_SIZE, CollisionLayer::Touch),
         GameEntity,
         GameDynamicEntity,
-        Sprite::from_atlas_image(
-            coin_texture,
-            TextureAtlas {
-                layout: layout_handle,
-                index: 0,
-            },
-        ),
         Transform::from_xyz(position.x, position.y, 20.0),
-        animation_indices,
-        AnimationTimer(Timer::from_seconds(0.1, TimerMode::Repeating)),
+        Position(position),
     ));
 }
 
+pub(super) fn collect_coins(
+    mut commands: Commands,
+    mut touched: MessageReader<BobTouched>,
+    coins_query: Query<&Position, With<Coin>>,
+    mut points: ResMut<Points>,
+    mut coins: ResMut<Coins>,
+    mut sound_events: MessageWriter<SoundEvent>,
+) {
+    for BobTouched(entity) in touched.read() {
+        let Ok(coin_position) = coins_query.get(*entity) else {
+            continue;
+        };
+
+        points.0 += COIN_SCORE;
+        coins.0 += 1;
+        sound_events.write(SoundEvent::at(Sound::Coin, coin_position.0));
+        commands.entity(*entity).despawn();
+    }
+}
+
+pub(super) fn add_coin_sprites(
+    mut commands: Commands,
+    coin_query: Query<Entity, Added<Coin>>,
+    game_assets: Res<GameAssets>,
+) {
+    for entity in &coin_query {
+        let animation_indices = AnimationIndices {
+            first: 0,
+            last: 2,
+            ..default()
+        };
+
+        commands.entity(entity).insert((
+            game_assets.coin_sprite(),
+            animation_indices,
+            AnimationTimer(Timer::from_seconds(0.1, TimerMode::Repeating)),
+        ));
+    }
+}
+
 pub(super) fn animate_coins(
     mut coins: Query<(&AnimationIndices, &mut AnimationTimer, &mut Sprite), With<Coin>>,
     time: Res<Time>,
@@ -0,0 +1,143 @@
+use bevy::{
+    math::bounding::{Aabb2d, BoundingCircle, IntersectsVolume},
+    prelude::*,
+};
+
+use super::{
+    bob::{BOB_SIZE, Bob},
+    level::WORLD_WIDTH,
+    physics::{Position, PreviousPosition},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColliderShape {
+    Rect(Vec2),
+    Circle(f32),
+}
+
+// How Bob collides with an object
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollisionLayer {
+    // Bob lands on it while falling
+    Ground,
+    // Bob touches it from any direction
+    Touch,
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Collider {
+    pub shape: ColliderShape,
+    pub layer: CollisionLayer,
+}
+
+impl Collider {
+    pub fn rect(size: Vec2, layer: CollisionLayer) -> Self {
+        Self {
+            shape: ColliderShape::Rect(size),
+            layer,
+        }
+    }
+
+    pub fn circle(radius: f32, layer: CollisionLayer) -> Self {
+        Self {
+            shape: ColliderShape::Circle(radius),
+            layer,
+        }
+    }
+
+    // Whether Bob's box at the given position overlaps the collider
+    pub fn touches(&self, position: Vec2, bob: &Aabb2d) -> bool {
+        match self.shape {
+            ColliderShape::Rect(size) => bob.intersects(&Aabb2d::new(position, size / 2.0)),
+            ColliderShape::Circle(radius) => bob.intersects(&BoundingCircle::new(position, radius)),
+        }
+    }
+
+    // How far along the way from `from` to `to` Bob's box first touches the
+    // collider, from 0.0 to 1.0. Circles are swept as their bounding square.
+    pub fn sweep(&self, position: Vec2, from: Vec2, to: Vec2) -> Option<f32> {
+        let half_size = BOB_SIZE / 2.0
+            + match self.shape {
+                ColliderShape::Rect(size) => size / 2.0,
+                ColliderShape::Circle(radius) => Vec2::splat(radius),
+            };
+        let min = position - half_size;
+        let max = position + half_size;
+        let delta = to - from;
+
+        let mut enter = 0.0_f32;
+        let mut exit = 1.0_f32;
+        for axis in 0..2 {
+            if delta[axis] == 0.0 {
+                if from[axis] < min[axis] || from[axis] > max[axis] {
+                    return None;
+                }
+            } else {
+                let near = (min[axis] - from[axis]) / delta[axis];
+                let far = (max[axis] - from[axis]) / delta[axis];
+                enter = enter.max(near.min(far));
+                exit = exit.min(near.max(far));
+            }
+        }
+        (enter <= exit).then_some(enter)
+    }
+}
+
+// Bob landed on a Ground collider
+#[derive(Message, Debug, Clone, Copy)]
+pub struct BobLandedOn(pub Entity);
+
+// Bob touched a Touch collider
+#[derive(Message, Debug, Clone, Copy)]
+pub struct BobTouched(pub Entity);
+
+// Finds the colliders Bob hits. Each object type reacts to the messages in
+// its own system.
+//
+// Landings are swept along Bob's movement in the tick, so he can't fall
+// through an object between two ticks. He lands on the first one in his way,
+// and if he went through it, he is moved back to where he touched it.
+pub(super) fn detect_collisions(
+    mut bob_query: Query<(&mut Position, &PreviousPosition, &Bob)>,
+    colliders_query: Query<(Entity, &Position, &Collider), Without<Bob>>,
+    mut landed: MessageWriter<BobLandedOn>,
+    mut touched: MessageWriter<BobTouched>,
+) {
+    let (mut bob_position, previous_position, bob) = bob_query.single_mut().unwrap();
+    let to = bob_position.0;
+    // Don't sweep across the screen when wrapping around
+    let from = if (to.x - previous_position.x).abs() < WORLD_WIDTH / 2.0 {
+        previous_position.0
+    } else {
+        Vec2::new(to.x, previous_position.y)
+    };
+    let bob_box = Aabb2d::new(to, BOB_SIZE / 2.0);
+    let falling = bob.velocity.y <= 0.0;
+
+    let mut landing: Option<(f32, Entity)> = None;
+    for (entity, position, collider) in &colliders_query {
+        match collider.layer {
+            CollisionLayer::Ground if falling => {
+                if let Some(time) = collider.sweep(position.0, from, to)
+                    && landing.is_none_or(|(first, _)| time < first)
+                {
+                    landing = Some((time, entity));
+                }
+            }
+            CollisionLayer::Ground => {}
+            CollisionLayer::Touch => {
+                if collider.touches(position.0, &bob_box) {
+                    touched.write(BobTouched(entity));
+                }
+            }
+        }
+    }
+
+    if let Some((time, entity)) = landing {
+        let (_, position, collider) = colliders_query.get(entity).unwrap();
+        if !collider.touches(position.0, &bob_box) {
+            bob_position.0 = from.lerp(to, time);
+        }
+        landed.write(BobLandedOn(entity));
+    }
+}
@@ -0,0 +1,402 @@
+use bevy::{
+    input::mouse::{AccumulatedMouseScroll, MouseScrollUnit},
+    prelude::*,
+    window::PrimaryWindow,
+};
+
+use crate::{Background, GameState, assets::GameAssets, cleanup, click_sound, focus::BackButton};
+
+use super::{
+    LevelOverride, Playtest,
+    castle::CASTLE_SIZE,
+    coin::COIN_SIZE,
+    level::{GRID_SIZE, GameObject, GameObjectType, LevelFile, WORLD_WIDTH, save_level},
+    platform::{PLATFORM_SIZE, PLATFORM_VELOCITY_X},
+    reset_camera,
+    spring::SPRING_SIZE,
+    squirrel::SQUIRREL_SIZE,
+};
+
+// World position of the bottom left corner of the level
+const LEVEL_ORIGIN: Vec2 = Vec2::new(-160.0, -240.0);
+const SCROLL_SPEED: f32 = 480.0;
+// Saved levels are named after the level file being played, if any
+const DEFAULT_LEVEL_NAME: &str = "custom";
+
+const TRANSPARENT: Color = Color::linear_rgba(0.0, 0.0, 0.0, 0.0);
+const SELECTED: Color = Color::linear_rgba(1.0, 1.0, 1.0, 0.3);
+const MOVING_PLATFORM_COLOR: Color = Color::srgb(0.6, 0.8, 1.0);
+
+const TOOLS: [(GameObjectType, &str); 6] = [
+    (GameObjectType::Platform(0.0), "PLATFORM"),
+    (GameObjectType::Platform(PLATFORM_VELOCITY_X), "MOVING"),
+    (GameObjectType::Spring, "SPRING"),
+    (GameObjectType::Coin, "COIN"),
+    (GameObjectType::Squirrel, "SQUIRREL"),
+    (GameObjectType::Castle, "CASTLE"),
+];
+
+#[derive(Component)]
+struct EditorEntity;
+
+// An object of the level being edited
+#[derive(Component)]
+struct EditorObject(GameObjectType);
+
+#[derive(Component)]
+enum EditorButtonAction {
+    Tool(GameObjectType),
+    Save,
+    PlayFromHere,
+    Back,
+}
+
+// The level being edited, kept while playtesting it
+#[derive(Resource, Default)]
+struct EditorLevel(Vec<GameObject>);
+
+// Type of the objects placed with the mouse
+#[derive(Resource)]
+struct EditorTool(GameObjectType);
+
+impl Default for EditorTool {
+    fn default() -> Self {
+        Self(TOOLS[0].0)
+    }
+}
+
+// Object being dragged with the mouse
+#[derive(Resource, Default)]
+struct Dragging(Option<Entity>);
+
+pub(super) struct EditorPlugin;
+impl Plugin for EditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EditorLevel>()
+            .init_resource::<EditorTool>()
+            .init_resource::<Dragging>()
+            .add_systems(OnEnter(GameState::Editor), setup_editor)
+            .add_systems(
+                OnExit(GameState::Editor),
+                (
+                    click_sound,
+                    store_level,
+                    cleanup::<EditorEntity>,
+                    reset_camera,
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    editor_action,
+                    highlight_tool.run_if(resource_changed::<EditorTool>),
+                    scroll_camera,
+                    edit_objects,
+                )
+                    .run_if(in_state(GameState::Editor)),
+            );
+    }
+}
+
+fn setup_editor(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    mut editor_level: ResMut<EditorLevel>,
+    level_override: Res<LevelOverride>,
+    tool: Res<EditorTool>,
+) {
+    // Start from the level file, if there's nothing being edited
+    if editor_level.0.is_empty()
+        && let Some(level) = &level_override.0
+    {
+        editor_level.0 = level.objects.clone();
+    }
+
+    for object in &editor_level.0 {
+        spawn_object(
+            &mut commands,
+            &game_assets,
+            object.object_type,
+            LEVEL_ORIGIN + Vec2::new(object.x, object.y),
+        );
+    }
+
+    let font = game_assets.font.clone();
+    let button = |parent: &mut ChildSpawnerCommands, action: EditorButtonAction, text: &str| {
+        let background = match action {
+            EditorButtonAction::Tool(object_type) if object_type == tool.0 => SELECTED,
+            _ => TRANSPARENT,
+        };
+        let is_back = matches!(action, EditorButtonAction::Back);
+        parent
+            .spawn((
+                Button,
+                Node {
+                    padding: UiRect::all(Val::Px(4.0)),
+                    ..default()
+                },
+                BackgroundColor(background),
+                action,
+            ))
+            .insert_if(BackButton, || is_back)
+            .with_children(|parent| {
+                parent.spawn((
+                    Text::new(text),
+                    TextFont {
+                        font: font.clone(),
+                        font_size: 16.0,
+                        ..default()
+                    },
+                    TextColor(Color::WHITE),
+                    TextLayout::new_with_justify(Justify::Center),
+                ));
+            });
+    };
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::SpaceBetween,
+                ..default()
+            },
+            EditorEntity,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(Node {
+                    flex_wrap: FlexWrap::Wrap,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for (object_type, text) in TOOLS {
+                        button(parent, EditorButtonAction::Tool(object_type), text);
+                    }
+                });
+
+            parent
+                .spawn(Node {
+                    justify_content: JustifyContent::SpaceAround,
+                    ..default()
+                })
+                .with_children(|parent| {
+                    button(parent, EditorButtonAction::Back, "BACK");
+                    button(parent, EditorButtonAction::Save, "SAVE");
+                    button(parent, EditorButtonAction::PlayFromHere, "PLAY FROM HERE");
+                });
+        });
+}
+
+fn spawn_object(
+    commands: &mut Commands,
+    game_assets: &GameAssets,
+    object_type: GameObjectType,
+    position: Vec2,
+) -> Entity {
+    let sprite = match object_type {
+        GameObjectType::Platform(speed) => {
+            let mut sprite = game_assets.platform_sprite();
+            if speed > 0.0 {
+                sprite.color = MOVING_PLATFORM_COLOR;
+            }
+            sprite
+        }
+        GameObjectType::Squirrel => game_assets.squirrel_sprite(),
+        GameObjectType::Coin => game_assets.coin_sprite(),
+        GameObjectType::Spring => Sprite::from_image(game_assets.spring.clone()),
+        GameObjectType::Castle => Sprite::from_image(game_assets.castle.clone()),
+    };
+
+    commands
+        .spawn((
+            EditorObject(object_type),
+            EditorEntity,
+            sprite,
+            Transform::from_xyz(position.x, position.y, 20.0),
+        ))
+        .id()
+}
+
+fn object_size(object_type: GameObjectType) -> Vec2 {
+    match object_type {
+        GameObjectType::Platform(_) => PLATFORM_SIZE,
+        GameObjectType::Squirrel => SQUIRREL_SIZE,
+        GameObjectType::Coin => COIN_SIZE,
+        GameObjectType::Spring => SPRING_SIZE,
+        GameObjectType::Castle => CASTLE_SIZE,
+    }
+}
+
+// Snaps a world position to the level's grid, inside the level
+fn snap_to_grid(position: Vec2) -> Vec2 {
+    let snapped = ((position - LEVEL_ORIGIN) / GRID_SIZE).round() * GRID_SIZE;
+    LEVEL_ORIGIN + snapped.clamp(Vec2::ZERO, Vec2::new(WORLD_WIDTH, f32::INFINITY))
+}
+
+fn level_objects(objects: &Query<(&EditorObject, &Transform)>) -> Vec<GameObject> {
+    let mut level: Vec<GameObject> = objects
+        .iter()
+        .map(|(object, transform)| {
+            let position = transform.translation.truncate() - LEVEL_ORIGIN;
+            GameObject {
+                object_type: object.0,
+                x: position.x,
+                y: position.y,
+                is_spawned: false,
+            }
+        })
+        .collect();
+    level.sort_by(|a, b| a.y.total_cmp(&b.y));
+    level
+}
+
+fn store_level(
+    objects: Query<(&EditorObject, &Transform)>,
+    mut editor_level: ResMut<EditorLevel>,
+    mut dragging: ResMut<Dragging>,
+) {
+    editor_level.0 = level_objects(&objects);
+    dragging.0 = None;
+}
+
+fn editor_action(
+    mut commands: Commands,
+    interaction_query: Query<
+        (&Interaction, &EditorButtonAction),
+        (Changed<Interaction>, With<Button>),
+    >,
+    objects: Query<(&EditorObject, &Transform)>,
+    camera_query: Query<&Transform, With<Camera>>,
+    mut tool: ResMut<EditorTool>,
+    mut game_state: ResMut<NextState<GameState>>,
+    level_file: Res<LevelFile>,
+) {
+    for (interaction, action) in &interaction_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match action {
+            EditorButtonAction::Tool(object_type) => tool.0 = *object_type,
+            EditorButtonAction::Save => {
+                let name = level_file.0.as_deref().unwrap_or(DEFAULT_LEVEL_NAME);
+                match save_level(name, &level_objects(&objects)) {
+                    Ok(path) => info!("Saved the level to {}", path.display()),
+                    Err(err) => error!("Couldn't save the level: {}", err),
+                }
+            }
+            EditorButtonAction::PlayFromHere => {
+                commands.insert_resource(Playtest {
+                    objects: level_objects(&objects),
+                    height: camera_query.single().unwrap().translation.y,
+                });
+                game_state.set(GameState::Playing);
+            }
+            EditorButtonAction::Back => game_state.set(GameState::Menu),
+        }
+    }
+}
+
+fn highlight_tool(
+    tool: Res<EditorTool>,
+    mut buttons: Query<(&EditorButtonAction, &mut BackgroundColor)>,
+) {
+    for (action, mut background) in &mut buttons {
+        if let EditorButtonAction::Tool(object_type) = action {
+            background.0 = if *object_type == tool.0 {
+                SELECTED
+            } else {
+                TRANSPARENT
+            };
+        }
+    }
+}
+
+fn scroll_camera(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mouse_scroll: Res<AccumulatedMouseScroll>,
+    mut camera_query: Query<&mut Transform, (With<Camera>, Without<Background>)>,
+    mut bg_query: Query<&mut Transform, (With<Background>, Without<Camera>)>,
+    time: Res<Time>,
+) {
+    let mut scroll = match mouse_scroll.unit {
+        MouseScrollUnit::Line => mouse_scroll.delta.y * GRID_SIZE,
+        MouseScrollUnit::Pixel => mouse_scroll.delta.y,
+    };
+    if keyboard_input.pressed(KeyCode::ArrowUp) || keyboard_input.pressed(KeyCode::KeyW) {
+        scroll += SCROLL_SPEED * time.delta_secs();
+    }
+    if keyboard_input.pressed(KeyCode::ArrowDown) || keyboard_input.pressed(KeyCode::KeyS) {
+        scroll -= SCROLL_SPEED * time.delta_secs();
+    }
+
+    let mut camera = camera_query.single_mut().unwrap();
+    camera.translation.y = (camera.translation.y + scroll).max(0.0);
+    bg_query.single_mut().unwrap().translation.y = camera.translation.y;
+}
+
+// Left click places an object, or drags the one under the cursor. Right click
+// deletes it.
+fn edit_objects(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    mouse_button_input: Res<ButtonInput<MouseButton>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    buttons: Query<&Interaction, With<Button>>,
+    mut objects: Query<(Entity, &EditorObject, &mut Transform)>,
+    mut dragging: ResMut<Dragging>,
+    tool: Res<EditorTool>,
+) {
+    if mouse_button_input.just_released(MouseButton::Left) {
+        dragging.0 = None;
+    }
+
+    let (camera, camera_transform) = camera_query.single().unwrap();
+    let Some(cursor) = window_query
+        .single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+        .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor).ok())
+    else {
+        return;
+    };
+
+    if let Some(entity) = dragging.0 {
+        if let Ok((_, _, mut transform)) = objects.get_mut(entity) {
+            let position = snap_to_grid(cursor);
+            transform.translation.x = position.x;
+            transform.translation.y = position.y;
+        }
+        return;
+    }
+
+    // Clicks on the buttons don't edit the level
+    if buttons
+        .iter()
+        .any(|interaction| *interaction != Interaction::None)
+    {
+        return;
+    }
+
+    let under_cursor = objects
+        .iter()
+        .find(|(_, object, transform)| {
+            let distance = (cursor - transform.translation.truncate()).abs();
+            distance.cmple(object_size(object.0) / 2.0).all()
+        })
+        .map(|(entity, _, _)| entity);
+
+    if mouse_button_input.just_pressed(MouseButton::Left) {
+        dragging.0 = Some(under_cursor.unwrap_or_else(|| {
+            spawn_object(&mut commands, &game_assets, tool.0, snap_to_grid(cursor))
+        }));
+    } else if mouse_button_input.just_pressed(MouseButton::Right)
+        && let Some(entity) = under_cursor
+    {
+        commands.entity(entity).despawn();
+    }
+}
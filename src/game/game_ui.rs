@@ -2,10 +2,17 @@ use bevy::prelude::*;
 
 use crate::{
     GameState,
-    highscores::{HighScores, check_and_update_highscores},
+    assets::GameAssets,
+    focus::BackButton,
+    highscores::{HighScores, PendingHighScore, PlayerName, after_run_state, check_new_highscore},
+    input::{Action, Actions},
+    leaderboard::Leaderboard,
 };
 
-use super::{GameEntity, PlayState, Points};
+use super::{
+    ActiveReplay, Coins, GameEntity, LevelSeed, PlayState, Points, Practice, RunLevel,
+    physics::CameraHeight, run_entry,
+};
 
 #[derive(Component)]
 pub(super) struct GameUi;
@@ -29,7 +36,7 @@ pub(super) enum PlayButtonAction {
 
 const TRANSPARENT: Color = Color::linear_rgba(0.0, 0.0, 0.0, 0.0);
 
-pub(super) fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
+pub(super) fn setup_ui(mut commands: Commands, game_assets: Res<GameAssets>) {
     // Spawn the game UI
     commands
         .spawn((
@@ -50,6 +57,7 @@ pub(super) fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
                 (PlayButtonAction::Resume, "RESUME", Visibility::Hidden),
                 (PlayButtonAction::Quit, "QUIT", Visibility::Hidden),
             ] {
+                let is_back = matches!(action, PlayButtonAction::Resume);
                 parent
                     .spawn((
                         Button,
@@ -58,11 +66,12 @@ pub(super) fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
                         GameButtonUi,
                         action,
                     ))
+                    .insert_if(BackButton, || is_back)
                     .with_children(|parent| {
                         parent.spawn((
                             Text::new(text),
                             TextFont {
-                                font: asset_server.load("fonts/Retroville NC.ttf"),
+                                font: game_assets.font.clone(),
                                 font_size: 40.0,
                                 ..default()
                             },
@@ -88,9 +97,7 @@ pub(super) fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
                     PlayButtonAction::Pause,
                 ))
                 .with_children(|parent| {
-                    let path = "sprites/pause.png";
-                    let icon = asset_server.load(path);
-                    parent.spawn(ImageNode::new(icon));
+                    parent.spawn(ImageNode::new(game_assets.pause.clone()));
                 });
 
             parent
@@ -109,7 +116,7 @@ pub(super) fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
                     parent.spawn((
                         Text::new("SCORE: 0"),
                         TextFont {
-                            font: asset_server.load("fonts/Retroville NC.ttf"),
+                            font: game_assets.font.clone(),
                             font_size: 30.0,
                             ..default()
                         },
@@ -123,17 +130,23 @@ pub(super) fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
 
 pub(super) fn spawn_game_over_ui(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
+    game_assets: Res<GameAssets>,
     game_ui_query: Query<Entity, With<GameUi>>,
     points: Res<Points>,
-    mut high_scores: ResMut<HighScores>,
+    level_seed: Res<LevelSeed>,
+    run_level: Res<RunLevel>,
+    practice: Res<Practice>,
+    high_scores: Res<HighScores>,
+    active_replay: Res<ActiveReplay>,
 ) {
     for entity in game_ui_query.iter() {
         commands.entity(entity).despawn();
     }
 
+    // The run is kept as a new high score once its end is processed
     let score = points.0;
-    let score_title = if check_and_update_highscores(&mut high_scores, score) {
+    let score_title = if !active_replay.is_playing() && !practice.0 && high_scores.qualifies(score)
+    {
         format!("NEW HIGHSCORE: {}", score)
     } else {
         format!("SCORE: {}", score)
@@ -165,7 +178,7 @@ pub(super) fn spawn_game_over_ui(
                     parent.spawn((
                         Text::new(score_title),
                         TextFont {
-                            font: asset_server.load("fonts/Retroville NC.ttf"),
+                            font: game_assets.font.clone(),
                             font_size: 30.0,
                             ..default()
                         },
@@ -173,6 +186,19 @@ pub(super) fn spawn_game_over_ui(
                         TextLayout::new_with_justify(Justify::Left),
                         ScoreUi,
                     ));
+                    parent.spawn((
+                        Text::new(match &run_level.0 {
+                            Some(level) => format!("LEVEL: {}", level.name),
+                            None => format!("SEED: {}", level_seed.0),
+                        }),
+                        TextFont {
+                            font: game_assets.font.clone(),
+                            font_size: 16.0,
+                            ..default()
+                        },
+                        TextColor(Color::WHITE),
+                        TextLayout::new_with_justify(Justify::Left),
+                    ));
                 });
 
             parent
@@ -185,7 +211,7 @@ pub(super) fn spawn_game_over_ui(
                     parent.spawn((
                         Text::new("GAME OVER"),
                         TextFont {
-                            font: asset_server.load("fonts/Retroville NC.ttf"),
+                            font: game_assets.font.clone(),
                             font_size: 40.0,
                             ..default()
                         },
@@ -250,6 +276,25 @@ pub(super) fn update_buttons_visibility(
     };
 }
 
+// Back pauses too while running. Once paused, it presses the resume button.
+pub(super) fn toggle_pause(
+    actions: Actions,
+    play_state: Res<State<PlayState>>,
+    mut next_play_state: ResMut<NextState<PlayState>>,
+) {
+    match play_state.get() {
+        PlayState::Running
+            if actions.just_pressed(Action::Pause) || actions.just_pressed(Action::Back) =>
+        {
+            next_play_state.set(PlayState::Paused)
+        }
+        PlayState::Paused if actions.just_pressed(Action::Pause) => {
+            next_play_state.set(PlayState::Running)
+        }
+        _ => {}
+    }
+}
+
 pub(super) fn ui_action(
     interaction_query: Query<
         (&Interaction, &PlayButtonAction),
@@ -258,17 +303,36 @@ pub(super) fn ui_action(
     mut game_state: ResMut<NextState<GameState>>,
     mut play_state: ResMut<NextState<PlayState>>,
     points: Res<Points>,
-    mut high_scores: ResMut<HighScores>,
+    coins: Res<Coins>,
+    camera_height: Res<CameraHeight>,
+    level_seed: Res<LevelSeed>,
+    run_level: Res<RunLevel>,
+    practice: Res<Practice>,
+    high_scores: Res<HighScores>,
+    mut pending: ResMut<PendingHighScore>,
+    leaderboard: Res<Leaderboard>,
+    player_name: Res<PlayerName>,
+    active_replay: Res<ActiveReplay>,
 ) {
     for (interaction, menu_button_action) in &interaction_query {
         if *interaction == Interaction::Pressed {
             match menu_button_action {
                 PlayButtonAction::Play => play_state.set(PlayState::Running),
                 PlayButtonAction::Resume => play_state.set(PlayState::Running),
+                // The score of a quit run still counts, but the run isn't
+                // kept as a replay
                 PlayButtonAction::Quit => {
-                    check_and_update_highscores(&mut high_scores, points.0);
+                    if !active_replay.is_playing() && !practice.0 {
+                        check_new_highscore(
+                            &high_scores,
+                            &mut pending,
+                            &leaderboard,
+                            &player_name,
+                            run_entry(points.0, &coins, &camera_height, &level_seed, &run_level),
+                        );
+                    }
                     play_state.set(PlayState::Ready);
-                    game_state.set(GameState::WinScreen); //TODO: Fix this
+                    game_state.set(after_run_state(&pending));
                 }
                 PlayButtonAction::Pause => play_state.set(PlayState::Paused),
             }
@@ -288,7 +352,8 @@ pub(super) fn update_score_text(
 pub(super) fn go_back_to_menu(
     mut game_state: ResMut<NextState<GameState>>,
     mut play_state: ResMut<NextState<PlayState>>,
+    pending: Res<PendingHighScore>,
 ) {
     play_state.set(PlayState::Ready);
-    game_state.set(GameState::Menu);
+    game_state.set(after_run_state(&pending));
 }
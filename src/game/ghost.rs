@@ -0,0 +1,187 @@
+use bevy::prelude::*;
+
+use crate::settings::{read_ghost, write_ghost};
+
+use super::{
+    ActiveReplay, GameEntity, LevelSeed, Points, Practice, RunFinished, RunLevel,
+    bob::Bob,
+    physics::{Position, PreviousPosition},
+};
+
+const GHOST_MAGIC: &[u8; 4] = b"SJG1";
+const GHOST_ALPHA: f32 = 0.4;
+
+#[derive(Clone, Copy)]
+struct GhostFrame {
+    position: Vec2,
+    index: u8,
+}
+
+// Bob's trajectory during a run, one frame per simulation tick
+#[derive(Default)]
+struct GhostTrack {
+    score: u32,
+    frames: Vec<GhostFrame>,
+}
+
+// Ghosts are kept per level, under the seed of a generated level or the name of
+// a hand-made one
+pub(super) fn ghost_name(level_seed: &LevelSeed, run_level: &RunLevel) -> String {
+    match &run_level.0 {
+        Some(level) => format!("level_{}", level.name),
+        None => level_seed.0.to_string(),
+    }
+}
+
+impl GhostTrack {
+    fn load(name: &str) -> Option<Self> {
+        let bytes = read_ghost(name)?;
+        let bytes = bytes.strip_prefix(GHOST_MAGIC)?;
+        let score = u32::from_le_bytes(bytes.get(0..4)?.try_into().ok()?);
+
+        let frames = bytes.get(4..)?;
+        if frames.len() % 9 != 0 {
+            return None;
+        }
+        let frames = frames
+            .chunks_exact(9)
+            .map(|frame| GhostFrame {
+                position: Vec2::new(
+                    f32::from_le_bytes(frame[0..4].try_into().unwrap()),
+                    f32::from_le_bytes(frame[4..8].try_into().unwrap()),
+                ),
+                index: frame[8],
+            })
+            .collect();
+
+        Some(Self { score, frames })
+    }
+
+    fn save(&self, name: &str) {
+        let mut bytes = Vec::from(*GHOST_MAGIC);
+        bytes.extend_from_slice(&self.score.to_le_bytes());
+        for frame in &self.frames {
+            bytes.extend_from_slice(&frame.position.x.to_le_bytes());
+            bytes.extend_from_slice(&frame.position.y.to_le_bytes());
+            bytes.push(frame.index);
+        }
+        write_ghost(name, &bytes);
+    }
+}
+
+// Translucent Bob following the best run on the current level
+#[derive(Component)]
+pub(super) struct Ghost;
+
+#[derive(Resource, Default)]
+pub(super) struct GhostRecording(GhostTrack);
+
+#[derive(Resource, Default)]
+pub(super) struct GhostPlayback {
+    track: GhostTrack,
+    tick: usize,
+}
+
+pub(super) fn spawn_ghost(
+    commands: &mut Commands,
+    playback: &mut GhostPlayback,
+    bob_sprite: &Sprite,
+    name: &str,
+) {
+    let Some(track) = GhostTrack::load(name) else {
+        return;
+    };
+    let Some(first) = track.frames.first() else {
+        return;
+    };
+
+    let mut sprite = bob_sprite.clone();
+    sprite.color = sprite.color.with_alpha(GHOST_ALPHA);
+
+    commands.spawn((
+        Ghost,
+        GameEntity,
+        sprite,
+        Position(first.position),
+        PreviousPosition(first.position),
+        Transform::from_xyz(first.position.x, first.position.y, 19.0),
+    ));
+
+    *playback = GhostPlayback { track, tick: 0 };
+}
+
+pub(super) fn record_ghost_frame(
+    bob_query: Query<(&Position, &Sprite), With<Bob>>,
+    mut recording: ResMut<GhostRecording>,
+    active_replay: Res<ActiveReplay>,
+) {
+    if active_replay.is_playing() {
+        return;
+    }
+
+    let (position, sprite) = bob_query.single().unwrap();
+    let index = sprite.texture_atlas.as_ref().map_or(0, |atlas| atlas.index);
+
+    recording.0.frames.push(GhostFrame {
+        position: position.0,
+        index: index as u8,
+    });
+}
+
+pub(super) fn move_ghost(
+    mut ghost_query: Query<(&mut Position, &mut Sprite, &mut Transform), With<Ghost>>,
+    mut playback: ResMut<GhostPlayback>,
+) {
+    let Ok((mut position, mut sprite, mut transform)) = ghost_query.single_mut() else {
+        return;
+    };
+
+    // Once the best run is over, the ghost stays where it ended
+    let Some(&frame) = playback.track.frames.get(playback.tick) else {
+        return;
+    };
+    playback.tick += 1;
+
+    // Face the direction of movement, ignoring the jump when wrapping around
+    let dx = frame.position.x - position.x;
+    if dx < 0.0 && dx > -160.0 {
+        transform.rotation = Quat::from_rotation_y(std::f32::consts::PI);
+    } else if dx > 0.0 && dx < 160.0 {
+        transform.rotation = Quat::default();
+    }
+    position.0 = frame.position;
+
+    if let Some(atlas) = &mut sprite.texture_atlas {
+        atlas.index = frame.index as usize;
+    }
+}
+
+pub(super) fn save_ghost(
+    mut recording: ResMut<GhostRecording>,
+    mut run_finished: MessageReader<RunFinished>,
+    level_seed: Res<LevelSeed>,
+    run_level: Res<RunLevel>,
+    practice: Res<Practice>,
+    points: Res<Points>,
+) {
+    let mut track = std::mem::take(&mut recording.0);
+    // Runs the player quit and practice runs are not kept
+    if run_finished.read().count() == 0 || practice.0 || track.frames.is_empty() {
+        return;
+    }
+
+    track.score = points.0;
+
+    let name = ghost_name(&level_seed, &run_level);
+    if GhostTrack::load(&name).is_none_or(|best| best.score < track.score) {
+        track.save(&name);
+    }
+}
+
+pub(super) fn reset_ghost(
+    mut recording: ResMut<GhostRecording>,
+    mut playback: ResMut<GhostPlayback>,
+) {
+    recording.0 = GhostTrack::default();
+    *playback = GhostPlayback::default();
+}
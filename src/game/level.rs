@@ -1,38 +1,91 @@
+use bevy::log::error;
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 
 use super::{bob, coin, platform, spring, squirrel};
 
-const WORLD_WIDTH: f32 = 10.0 * 32.0;
-const WORLD_HEIGHT: f32 = 15.0 * 32.0 * 20.0;
+pub use check::{NoPathFound, check_level};
+pub use file::{LevelAsset, LevelFile, LevelFilePlugin, save_level};
 
+mod check;
+mod file;
+
+// Objects are laid out in units of 32 pixels
+pub const GRID_SIZE: f32 = 32.0;
+pub const WORLD_WIDTH: f32 = 10.0 * GRID_SIZE;
+const WORLD_HEIGHT: f32 = 15.0 * GRID_SIZE * 20.0;
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum GameObjectType {
-    Platform(bool),
+    // Horizontal speed of the platform, 0 for a static one
+    Platform(f32),
     Squirrel,
     Coin,
     Spring,
     Castle,
 }
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct GameObject {
     pub object_type: GameObjectType,
     pub x: f32,
     pub y: f32,
+    #[serde(skip)]
     pub is_spawned: bool,
 }
 
-pub fn generate_level() -> Vec<GameObject> {
+// A level made in the editor, played instead of a generated one. Runs on it are
+// told apart from runs on generated levels by its name.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct HandMadeLevel {
+    pub name: String,
+    pub objects: Vec<GameObject>,
+}
+
+// Levels generated before giving up on finding one with a path to the castle
+const MAX_ATTEMPTS: usize = 100;
+
+// Generates levels until check_level finds a path to the castle, up to
+// MAX_ATTEMPTS, returning the last one anyway if none has a path.
+pub fn generate_level(rng: &mut (impl Rng + Clone)) -> Vec<GameObject> {
+    let mut objects = generate_candidate_level(rng);
+    // Retries draw from a copy, so the rolls of the run don't depend on them
+    let mut retry_rng = rng.clone();
+    for _ in 1..MAX_ATTEMPTS {
+        if check_level(&objects).is_ok() {
+            return objects;
+        }
+        objects = generate_candidate_level(&mut retry_rng);
+    }
+
+    if let Err(no_path) = check_level(&objects) {
+        error!(
+            "No level with a path in {} attempts, playing one that gets stuck at {}",
+            MAX_ATTEMPTS, no_path.stuck_at
+        );
+    }
+    objects
+}
+
+// A random level, which is not checked for a path to the castle
+pub fn generate_candidate_level(rng: &mut impl Rng) -> Vec<GameObject> {
     let mut objects: Vec<GameObject> = Vec::new();
     let mut y: f32 = platform::PLATFORM_HEIGHT / 2.0;
     let max_jump_height: f32 =
         bob::BOB_JUMP_VELOCITY * bob::BOB_JUMP_VELOCITY / (2.0 * -bob::GRAVITY_Y);
-    let mut rng = rand::thread_rng();
     let is_spawned = false;
     while y < WORLD_HEIGHT - WORLD_WIDTH / 2.0 {
         let moving = rng.gen_range(0.0..1.0) > 0.8;
+        let speed = if moving {
+            platform::PLATFORM_VELOCITY_X
+        } else {
+            0.0
+        };
         let x = rng.gen_range(0.0..1.0) * (WORLD_WIDTH - platform::PLATFORM_WIDTH)
             + platform::PLATFORM_WIDTH / 2.0;
 
         objects.push(GameObject {
-            object_type: GameObjectType::Platform(moving),
+            object_type: GameObjectType::Platform(speed),
             x,
             y,
             is_spawned,
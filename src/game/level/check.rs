@@ -0,0 +1,339 @@
+use bevy::math::Vec2;
+
+use super::{GameObject, GameObjectType, WORLD_WIDTH};
+use crate::game::{
+    bob::{self, BOB_SIZE},
+    castle, physics, platform, spring, squirrel,
+};
+
+const TICK: f32 = (1.0 / physics::TICKS_PER_SECOND) as f32;
+// Height of the camera when a run starts. Bob can't fall until it moves up.
+const START_CAMERA_HEIGHT: f32 = 240.0;
+// Bob falls to his death this far below the camera
+const FALL_DISTANCE: f32 = 240.0;
+const BOB_SPEED_X: f32 = bob::ACCELERATION_X * bob::BOB_MOVE_VELOCITY;
+// Bob's position across the world is followed in cells he can cross in a tick
+const CELLS: usize = (WORLD_WIDTH / (BOB_SPEED_X * TICK)) as usize + 1;
+const CELL_WIDTH: f32 = WORLD_WIDTH / CELLS as f32;
+
+// No path to the castle was found from the start of the level
+#[derive(Debug)]
+pub struct NoPathFound {
+    // Height of the highest platform or spring Bob can get to
+    pub stuck_at: f32,
+}
+
+// Something Bob can jump off
+struct Surface {
+    x: f32,
+    y: f32,
+    half_size: Vec2,
+    jump_velocity: f32,
+    // Moving platforms may break up, so Bob can't wait on them
+    speed: f32,
+}
+
+impl Surface {
+    fn new(object: &GameObject) -> Option<Self> {
+        let (half_size, jump_velocity, speed) = match object.object_type {
+            GameObjectType::Platform(speed) => {
+                (platform::PLATFORM_SIZE / 2.0, bob::BOB_JUMP_VELOCITY, speed)
+            }
+            GameObjectType::Spring => {
+                (spring::SPRING_SIZE / 2.0, spring::SPRING_JUMP_VELOCITY, 0.0)
+            }
+            _ => return None,
+        };
+
+        Some(Self {
+            x: object.x,
+            y: object.y,
+            half_size,
+            jump_velocity,
+            speed,
+        })
+    }
+
+    // Bob bounces off the floor at the start, wherever he is
+    fn floor() -> Self {
+        Self {
+            x: WORLD_WIDTH / 2.0,
+            y: 0.0,
+            half_size: Vec2::new(WORLD_WIDTH / 2.0, 0.0),
+            jump_velocity: bob::BOB_JUMP_VELOCITY,
+            speed: 0.0,
+        }
+    }
+
+    fn takeoff_height(&self) -> f32 {
+        self.y + self.half_size.y + BOB_SIZE.y / 2.0
+    }
+
+    // Cells Bob can take off from. He can bounce to any point of the surface
+    // before jumping.
+    fn takeoffs(&self) -> Vec<bool> {
+        let reach = self.half_size.x + BOB_SIZE.x / 2.0;
+        (0..CELLS)
+            .map(|cell| wrapped_offset(cell_x(cell), self.x).abs() <= reach)
+            .collect()
+    }
+
+    fn target(&self) -> Target {
+        Target {
+            x: self.x,
+            y: self.y,
+            half_width: self.half_size.x,
+            reach: self.half_size + BOB_SIZE / 2.0,
+            speed: self.speed,
+            landing: true,
+        }
+    }
+}
+
+struct Target {
+    x: f32,
+    y: f32,
+    half_width: f32,
+    // Largest distance from Bob's center that touches the target
+    reach: Vec2,
+    speed: f32,
+    // Platforms and springs only count when Bob falls on them
+    landing: bool,
+}
+
+impl Target {
+    // Moving platforms may be going either way when Bob takes off
+    fn directions(&self) -> &'static [f32] {
+        if self.speed > 0.0 {
+            &[1.0, -1.0]
+        } else {
+            &[1.0]
+        }
+    }
+
+    // Where the target is, the given time into the jump
+    fn x_at(&self, time: f32, direction: f32) -> f32 {
+        if self.speed > 0.0 {
+            patrol(self.x, direction * self.speed * time, self.half_width)
+        } else {
+            self.x
+        }
+    }
+}
+
+// A squirrel, which patrols from side to side
+struct Squirrel {
+    x: f32,
+    y: f32,
+}
+
+impl Squirrel {
+    const REACH: Vec2 = Vec2::new(
+        (BOB_SIZE.x + squirrel::SQUIRREL_WIDTH) / 2.0,
+        (BOB_SIZE.y + squirrel::SQUIRREL_HEIGHT) / 2.0,
+    );
+    const HALF_WIDTH: f32 = squirrel::SQUIRREL_WIDTH / 2.0;
+
+    // Whether Bob may touch the squirrel, the given time into a jump off a
+    // moving platform. The squirrel is taken to be at its place in the level
+    // when he takes off, and may be anywhere it can have gone from there
+    // since, going either way.
+    fn touches(&self, bob: Vec2, time: f32) -> bool {
+        let distance = squirrel::SQUIRREL_VELOCITY_X * time;
+        let left = (self.x - distance).max(Self::HALF_WIDTH);
+        let right = (self.x + distance).min(WORLD_WIDTH - Self::HALF_WIDTH);
+        (bob.y - self.y).abs() < Self::REACH.y
+            && [-WORLD_WIDTH, 0.0, WORLD_WIDTH].iter().any(|shift| {
+                let x = bob.x + shift;
+                x > left - Self::REACH.x && x < right + Self::REACH.x
+            })
+    }
+}
+
+// Looks for a path from the floor to the castle, by simulating Bob's jumps
+// between platforms and springs. Bob's height follows the game's physics tick
+// by tick. Across the world, every place he can steer to at full speed is
+// followed, wrapping around the edges.
+//
+// This is a heuristic, not a proof that the level can be completed. Moving
+// objects start moving as soon as they spawn, and the check doesn't know
+// where they are in their patrol when Bob gets to them. Moving
+// platforms are taken to be at their place in the level when Bob takes off,
+// going either way at their speed and turning at the edges. Bob can wait on
+// a static surface until the target goes his way, so it only has to be
+// caught in one direction, but in both from another moving platform, which
+// may break up under him.
+//
+// The same goes for squirrels. Bob can wait on a static surface until the
+// squirrels are out of his way, so they only block jumps off moving
+// platforms. There, he must stay clear of the box each squirrel sweeps from
+// its place in the level for as long as he has been in the air.
+pub fn check_level(objects: &[GameObject]) -> Result<(), NoPathFound> {
+    // The floor is only used at the start and can't be landed on again
+    let surfaces: Vec<Surface> = std::iter::once(Surface::floor())
+        .chain(objects.iter().filter_map(Surface::new))
+        .collect();
+    let squirrels: Vec<Squirrel> = objects
+        .iter()
+        .filter(|object| matches!(object.object_type, GameObjectType::Squirrel))
+        .map(|object| Squirrel {
+            x: object.x,
+            y: object.y,
+        })
+        .collect();
+    let castles: Vec<Target> = objects
+        .iter()
+        .filter(|object| matches!(object.object_type, GameObjectType::Castle))
+        .map(|object| Target {
+            x: object.x,
+            y: object.y,
+            half_width: castle::CASTLE_SIZE.x / 2.0,
+            reach: (castle::CASTLE_SIZE + BOB_SIZE) / 2.0,
+            speed: 0.0,
+            landing: false,
+        })
+        .collect();
+
+    // The lowest camera height Bob can land on each surface with. A higher
+    // camera only makes falling to death more likely.
+    let mut cameras = vec![f32::INFINITY; surfaces.len()];
+    cameras[0] = START_CAMERA_HEIGHT;
+    let mut pending = vec![0];
+    let mut stuck_at: f32 = 0.0;
+
+    while let Some(from) = pending.pop() {
+        let source = &surfaces[from];
+        let camera = cameras[from];
+        stuck_at = stuck_at.max(source.y);
+
+        if castles
+            .iter()
+            .any(|castle| jump(source, camera, castle, &squirrels).is_some())
+        {
+            return Ok(());
+        }
+
+        for (to, target) in surfaces.iter().enumerate().skip(1) {
+            if let Some(camera) = jump(source, camera, &target.target(), &squirrels)
+                && camera < cameras[to]
+            {
+                cameras[to] = camera;
+                pending.push(to);
+            }
+        }
+    }
+
+    Err(NoPathFound { stuck_at })
+}
+
+// Bob jumps off the source towards the target. Returns the camera height once
+// he gets there.
+fn jump(source: &Surface, camera: f32, target: &Target, squirrels: &[Squirrel]) -> Option<f32> {
+    let velocity = source.jump_velocity;
+    let max_height = source.takeoff_height() + velocity * velocity / (2.0 * -bob::GRAVITY_Y);
+    if target.y - target.reach.y > max_height || target.y + target.reach.y <= camera - FALL_DISTANCE
+    {
+        return None;
+    }
+
+    // Bob can wait out squirrels on a static surface, and only squirrels
+    // within the height of the jump can be in the way
+    let lowest = (target.y - target.reach.y).min(source.takeoff_height());
+    let squirrels: Vec<&Squirrel> = squirrels
+        .iter()
+        .filter(|squirrel| {
+            source.speed > 0.0
+                && squirrel.y + Squirrel::REACH.y > lowest
+                && squirrel.y - Squirrel::REACH.y < max_height
+        })
+        .collect();
+
+    let mut cameras = target
+        .directions()
+        .iter()
+        .map(|&direction| fly(source, camera, target, direction, &squirrels));
+
+    if source.speed > 0.0 {
+        cameras.try_fold(camera, |highest, camera| Some(highest.max(camera?)))
+    } else {
+        cameras.flatten().reduce(f32::min)
+    }
+}
+
+// Follows Bob tick by tick from the source until he gets to the target.
+// Returns the camera height once he does, or None if he misses it, falls to
+// his death or may touch a squirrel wherever he goes.
+fn fly(
+    source: &Surface,
+    camera: f32,
+    target: &Target,
+    direction: f32,
+    squirrels: &[&Squirrel],
+) -> Option<f32> {
+    // The cells Bob can be in without having touched a squirrel
+    let mut cells = source.takeoffs();
+    let mut y = source.takeoff_height();
+    let mut velocity = source.jump_velocity;
+    let mut camera = camera;
+    let mut time = 0.0;
+
+    loop {
+        time += TICK;
+        velocity += bob::GRAVITY_Y * TICK;
+        y += velocity * TICK;
+        camera = camera.max(y);
+
+        // Bob can steer one cell either way, or stay where he is
+        cells = (0..CELLS)
+            .map(|cell| {
+                [CELLS - 1, 0, 1]
+                    .iter()
+                    .any(|step| cells[(cell + step) % CELLS])
+                    && !squirrels
+                        .iter()
+                        .any(|squirrel| squirrel.touches(Vec2::new(cell_x(cell), y), time))
+            })
+            .collect();
+        if !cells.contains(&true) {
+            return None;
+        }
+
+        let target_x = target.x_at(time, direction);
+        if (!target.landing || velocity <= 0.0)
+            && (y - target.y).abs() < target.reach.y
+            && (0..CELLS).any(|cell| {
+                cells[cell] && wrapped_offset(cell_x(cell), target_x).abs() < target.reach.x
+            })
+        {
+            return Some(camera);
+        }
+
+        let fallen = camera > START_CAMERA_HEIGHT && y <= camera - FALL_DISTANCE;
+        if fallen || (velocity <= 0.0 && y < target.y - target.reach.y) {
+            return None;
+        }
+    }
+}
+
+// Center of one of the cells Bob's position is followed in
+fn cell_x(cell: usize) -> f32 {
+    (cell as f32 + 0.5) * CELL_WIDTH
+}
+
+// Where an object moving the given distance from x ends up, turning at the
+// edges of the world
+fn patrol(x: f32, distance: f32, half_width: f32) -> f32 {
+    let track = WORLD_WIDTH - 2.0 * half_width;
+    let offset = (x - half_width + distance).rem_euclid(2.0 * track);
+    half_width
+        + if offset > track {
+            2.0 * track - offset
+        } else {
+            offset
+        }
+}
+
+// Shortest way from a to b around the wrap-around, negative to the left
+fn wrapped_offset(a: f32, b: f32) -> f32 {
+    (b - a + WORLD_WIDTH / 2.0).rem_euclid(WORLD_WIDTH) - WORLD_WIDTH / 2.0
+}
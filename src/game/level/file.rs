@@ -0,0 +1,157 @@
+use std::{error::Error, fs, path::PathBuf};
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    prelude::*,
+};
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+
+use super::{GameObject, HandMadeLevel, check_level};
+use crate::{
+    GameState,
+    game::{
+        GameObjects, LevelOverride, Practice, castle::Castle, coin::Coin, platform::Platform,
+        spring::Spring, squirrel::Squirrel,
+    },
+};
+
+const ASSETS_DIR: &str = "assets";
+const LEVELS_DIR: &str = "levels";
+const LEVEL_EXTENSION: &str = "level.ron";
+
+// A hand-made level, stored in assets/levels/<name>.level.ron
+#[derive(Asset, TypePath, Serialize, Deserialize)]
+pub struct LevelAsset {
+    pub objects: Vec<GameObject>,
+}
+
+// Name of the level file given on the command line, played instead of a
+// generated level
+#[derive(Resource, Default)]
+pub struct LevelFile(pub Option<String>);
+
+#[derive(Resource)]
+struct LevelHandle(Handle<LevelAsset>);
+
+#[derive(Default)]
+struct LevelLoader;
+
+impl AssetLoader for LevelLoader {
+    type Asset = LevelAsset;
+    type Settings = ();
+    type Error = Box<dyn Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &[LEVEL_EXTENSION]
+    }
+}
+
+// Writes a level file, which is hot-reloaded if it's the level being played.
+// Returns the path of the file.
+pub fn save_level(name: &str, objects: &[GameObject]) -> Result<PathBuf, Box<dyn Error>> {
+    let level = LevelAsset {
+        objects: objects.to_vec(),
+    };
+    let path = PathBuf::from(ASSETS_DIR)
+        .join(LEVELS_DIR)
+        .join(format!("{}.{}", name, LEVEL_EXTENSION));
+
+    fs::create_dir_all(path.parent().unwrap())?;
+    fs::write(
+        &path,
+        ron::ser::to_string_pretty(&level, PrettyConfig::default())?,
+    )?;
+    Ok(path)
+}
+
+// Loads the level file, if one was given, and keeps the game in sync with it
+// while it is edited
+pub struct LevelFilePlugin;
+impl Plugin for LevelFilePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<LevelAsset>()
+            .init_asset_loader::<LevelLoader>()
+            .init_resource::<LevelFile>()
+            .add_systems(Startup, load_level_file)
+            .add_systems(
+                Update,
+                update_level_override.run_if(resource_exists::<LevelHandle>),
+            );
+    }
+}
+
+fn load_level_file(
+    mut commands: Commands,
+    level_file: Res<LevelFile>,
+    asset_server: Res<AssetServer>,
+) {
+    if let Some(name) = &level_file.0 {
+        let path = format!("{}/{}.{}", LEVELS_DIR, name, LEVEL_EXTENSION);
+        commands.insert_resource(LevelHandle(asset_server.load(path)));
+    }
+}
+
+// Plays the level once it's loaded and every time the file changes. A run in
+// progress continues in the changed level, as practice, since it no longer
+// matches the level it started on.
+fn update_level_override(
+    mut commands: Commands,
+    mut asset_events: MessageReader<AssetEvent<LevelAsset>>,
+    levels: Res<Assets<LevelAsset>>,
+    level_handle: Res<LevelHandle>,
+    level_file: Res<LevelFile>,
+    mut level_override: ResMut<LevelOverride>,
+    mut practice: ResMut<Practice>,
+    mut game_objects: ResMut<GameObjects>,
+    game_state: Res<State<GameState>>,
+    level_objects: Query<
+        Entity,
+        Or<(
+            With<Platform>,
+            With<Spring>,
+            With<Coin>,
+            With<Squirrel>,
+            With<Castle>,
+        )>,
+    >,
+) {
+    for event in asset_events.read() {
+        if !event.is_added(&level_handle.0) && !event.is_modified(&level_handle.0) {
+            continue;
+        }
+        let Some(level) = levels.get(&level_handle.0) else {
+            continue;
+        };
+
+        if let Err(no_path) = check_level(&level.objects) {
+            warn!(
+                "No path to the castle found, Bob gets stuck at height {:.0}",
+                no_path.stuck_at
+            );
+        }
+        level_override.0 = Some(HandMadeLevel {
+            name: level_file.0.clone().unwrap_or_default(),
+            objects: level.objects.clone(),
+        });
+
+        if *game_state == GameState::Playing {
+            practice.0 = true;
+            for entity in &level_objects {
+                commands.entity(entity).despawn();
+            }
+            game_objects.0 = level.objects.clone();
+        }
+    }
+}
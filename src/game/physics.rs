@@ -0,0 +1,42 @@
+use bevy::prelude::*;
+
+pub const TICKS_PER_SECOND: f64 = 60.0;
+
+// Position of an object in the simulation. Transform is only used for
+// rendering and is interpolated from the last two simulated positions.
+#[derive(Component, Default, Clone, Copy, Deref, DerefMut)]
+pub struct Position(pub Vec2);
+
+#[derive(Component, Default, Clone, Copy, Deref, DerefMut)]
+pub struct PreviousPosition(pub Vec2);
+
+// Height of the camera in the simulation. It only moves upwards, following Bob.
+#[derive(Resource, Default)]
+pub struct CameraHeight(pub f32);
+
+pub(super) fn store_previous_positions(
+    mut query: Query<(&Position, &mut PreviousPosition), With<PreviousPosition>>,
+) {
+    for (position, mut previous) in &mut query {
+        previous.0 = position.0;
+    }
+}
+
+pub(super) fn interpolate_transforms(
+    mut query: Query<(&Position, Option<&PreviousPosition>, &mut Transform)>,
+    fixed_time: Res<Time<Fixed>>,
+) {
+    let alpha = fixed_time.overstep_fraction();
+
+    for (position, previous, mut transform) in &mut query {
+        let rendered = match previous {
+            // Don't interpolate across the screen when wrapping around
+            Some(previous) if (position.x - previous.x).abs() < 160.0 => {
+                previous.lerp(position.0, alpha)
+            }
+            _ => position.0,
+        };
+        transform.translation.x = rendered.x;
+        transform.translation.y = rendered.y;
+    }
+}
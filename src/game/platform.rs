@@ -1,18 +1,27 @@
-use crate::game::anim::{AnimationIndices, AnimationTimer};
-
-use super::{GameDynamicEntity, GameEntity, MovingObject};
+use super::{
+    GameDynamicEntity, GameEntity, MovingObject,
+    bob::{BOB_JUMP_VELOCITY, Bob},
+    collision::{BobLandedOn, Collider, CollisionLayer},
+    physics::{Position, PreviousPosition},
+    simulation::GameRng,
+};
+use crate::{Sound, SoundEvent, assets::GameAssets};
 use bevy::prelude::*;
+use rand::Rng;
 
 //const PLATFORM_ANIMATION_SPEED: f32 = 10.0;
 pub const PLATFORM_HEIGHT: f32 = 16.0;
 pub const PLATFORM_WIDTH: f32 = 64.0;
 pub const PLATFORM_SIZE: Vec2 = Vec2::new(PLATFORM_WIDTH, PLATFORM_HEIGHT);
-const PLATFORM_VELOCITY_X: f32 = 60.0;
+pub const PLATFORM_VELOCITY_X: f32 = 60.0;
+const PLATFORM_PULVERIZE_FRAME_TIME: f32 = 0.1;
+const PLATFORM_PULVERIZE_TIME: f32 = 5.0 * PLATFORM_PULVERIZE_FRAME_TIME;
 
 #[derive(Debug, Clone, Copy, Default, PartialEq)]
 pub enum PlatformState {
     #[default]
     Normal,
+    // Seconds since the platform started breaking up
     Pulverizing(f32),
 }
 
@@ -21,88 +30,91 @@ pub struct Platform {
     pub state: PlatformState,
 }
 
-pub(super) fn spawn_platform(
-    commands: &mut Commands,
-    asset_server: &Res<AssetServer>,
-    texture_atlases: &mut ResMut<Assets<TextureAtlasLayout>>,
-    moving: bool,
-    position: Vec2,
-) {
-    // Load the platform's sprite sheet and create a texture atlas from it
-    let platform_texture = asset_server.load("sprites/platform.png");
-    let layout_handle = texture_atlases.add(TextureAtlasLayout::from_grid(
-        UVec2::new(64, 16),
-        1,
-        4,
-        None,
-        None,
-    ));
-
-    let animation_indices = AnimationIndices {
-        first: 0,
-        last: 4,
-        ..default()
-    };
-
+pub(super) fn spawn_platform(commands: &mut Commands, speed: f32, position: Vec2) {
     // Spawn platform
-    let sprite_bundle = (
-        Sprite::from_atlas_image(
-            platform_texture,
-            TextureAtlas {
-                layout: layout_handle,
-                index: 0,
-            },
-        ),
+    let bundle = (
+        Platform { ..default() },
+        Collider::rect(PLATFORM_SIZE, CollisionLayer::Ground),
+        GameEntity,
         Transform::from_xyz(position.x, position.y, 20.0),
+        Position(position),
     );
 
-    if moving {
+    if speed > 0.0 {
         commands.spawn((
-            Platform { ..default() },
-            GameEntity,
+            bundle,
             GameDynamicEntity,
             MovingObject {
                 width: PLATFORM_WIDTH,
-                velocity_x: PLATFORM_VELOCITY_X,
+                velocity_x: speed,
                 dir: 1.0,
             },
-            sprite_bundle,
-            animation_indices,
-            AnimationTimer(Timer::from_seconds(0.1, TimerMode::Repeating)),
+            PreviousPosition(position),
         ));
     } else {
-        commands.spawn((Platform { ..default() }, GameEntity, sprite_bundle));
+        commands.spawn(bundle);
+    }
+}
+
+pub(super) fn add_platform_sprites(
+    mut commands: Commands,
+    platform_query: Query<Entity, Added<Platform>>,
+    game_assets: Res<GameAssets>,
+) {
+    for entity in &platform_query {
+        commands
+            .entity(entity)
+            .insert(game_assets.platform_sprite());
+    }
+}
+
+// Bob jumps off the platform he lands on, which may start breaking up
+pub(super) fn land_on_platforms(
+    mut landed: MessageReader<BobLandedOn>,
+    mut bob_query: Query<(&Position, &mut Bob)>,
+    mut platforms_query: Query<&mut Platform>,
+    mut sound_events: MessageWriter<SoundEvent>,
+    mut game_rng: ResMut<GameRng>,
+) {
+    let (bob_position, mut bob) = bob_query.single_mut().unwrap();
+    for BobLandedOn(entity) in landed.read() {
+        let Ok(mut platform) = platforms_query.get_mut(*entity) else {
+            continue;
+        };
+
+        bob.velocity.y = BOB_JUMP_VELOCITY;
+
+        sound_events.write(SoundEvent::at(Sound::Jump, bob_position.0));
+
+        if game_rng.0.gen_range(0.0..1.0) > 0.5 {
+            platform.state = PlatformState::Pulverizing(0.0);
+        }
     }
 }
 
-pub(super) fn animate_platforms(
+// Only moving platforms break up, static ones stay in place
+pub(super) fn pulverize_platforms(
     mut commands: Commands,
-    //mut platform_query: Query<(Entity, &Platform), With<Platform>>,
-    mut platform_query: Query<
-        (
-            &AnimationIndices,
-            &mut AnimationTimer,
-            Entity,
-            &mut Sprite,
-            &Platform,
-        ),
-        With<Platform>,
-    >,
+    mut platform_query: Query<(Entity, &mut Platform), With<MovingObject>>,
     time: Res<Time>,
 ) {
-    for (indices, mut timer, entity, mut sprite, platform) in &mut platform_query {
-        if let PlatformState::Pulverizing(_start_time) = platform.state {
-            timer.tick(time.delta());
+    for (entity, mut platform) in &mut platform_query {
+        if let PlatformState::Pulverizing(elapsed) = &mut platform.state {
+            *elapsed += time.delta_secs();
 
-            if timer.just_finished()
-                && let Some(atlas) = &mut sprite.texture_atlas
-            {
-                if atlas.index == indices.last {
-                    commands.entity(entity).despawn();
-                } else {
-                    atlas.index += 1;
-                }
+            if *elapsed >= PLATFORM_PULVERIZE_TIME {
+                commands.entity(entity).despawn();
             }
         }
     }
 }
+
+pub(super) fn animate_platforms(mut platform_query: Query<(&mut Sprite, &Platform)>) {
+    for (mut sprite, platform) in &mut platform_query {
+        if let PlatformState::Pulverizing(elapsed) = platform.state
+            && let Some(atlas) = &mut sprite.texture_atlas
+        {
+            atlas.index = ((elapsed / PLATFORM_PULVERIZE_FRAME_TIME) as usize).min(3);
+        }
+    }
+}
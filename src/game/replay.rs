@@ -0,0 +1,294 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    input::{Action, Actions, TouchSteering},
+    settings::{ReplaySlot, read_replay, write_replay},
+};
+
+use super::{LevelSeed, Practice, RunFinished, RunLevel, RunOutcome, level::HandMadeLevel};
+
+const REPLAY_MAGIC: &[u8; 4] = b"SJR3";
+// Replays of generated levels only
+const REPLAY_MAGIC_V2: &[u8; 4] = b"SJR2";
+// Replays without the outcome of the run
+const REPLAY_MAGIC_V1: &[u8; 4] = b"SJR1";
+// Stick positions closer to the center are taken as no input
+const STICK_DEADZONE: f32 = 0.15;
+
+// Horizontal input of the current tick, from -1.0 (left) to 1.0 (right)
+#[derive(Resource, Default)]
+pub struct MoveInput(pub f32);
+
+// A complete run: the level, the input of every simulated tick and the
+// result the run claims
+#[derive(Default, Clone)]
+pub struct Replay {
+    pub seed: u64,
+    // The hand-made level the run was played on, None for the level generated
+    // from the seed
+    pub level: Option<HandMadeLevel>,
+    pub score: u32,
+    // None for replays of older versions, which also kept runs the player quit
+    pub outcome: Option<RunOutcome>,
+    pub inputs: Vec<i8>,
+}
+
+fn outcome_to_byte(outcome: Option<RunOutcome>) -> u8 {
+    match outcome {
+        None => 0,
+        Some(RunOutcome::ReachedCastle) => 1,
+        Some(RunOutcome::Died) => 2,
+    }
+}
+
+fn outcome_from_byte(byte: u8) -> Option<Option<RunOutcome>> {
+    match byte {
+        0 => Some(None),
+        1 => Some(Some(RunOutcome::ReachedCastle)),
+        2 => Some(Some(RunOutcome::Died)),
+        _ => None,
+    }
+}
+
+impl Replay {
+    pub fn load(slot: ReplaySlot) -> Option<Self> {
+        Self::from_bytes(&read_replay(slot)?)
+    }
+
+    // A hand-made level is stored as its length and its RON, an empty one
+    // for a generated level. Inputs are stored run-length encoded as (count,
+    // value) pairs, as they rarely change from one tick to the next.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::from(*REPLAY_MAGIC);
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.extend_from_slice(&self.score.to_le_bytes());
+        bytes.push(outcome_to_byte(self.outcome));
+
+        let level = self
+            .level
+            .as_ref()
+            .and_then(|level| ron::to_string(level).ok())
+            .unwrap_or_default();
+        bytes.extend_from_slice(&(level.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(level.as_bytes());
+
+        let mut inputs = self.inputs.iter().peekable();
+        while let Some(&value) = inputs.next() {
+            let mut count: u16 = 1;
+            while count < u16::MAX && inputs.next_if_eq(&&value).is_some() {
+                count += 1;
+            }
+            bytes.extend_from_slice(&count.to_le_bytes());
+            bytes.push(value as u8);
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let (bytes, version) = if let Some(bytes) = bytes.strip_prefix(REPLAY_MAGIC) {
+            (bytes, 3)
+        } else if let Some(bytes) = bytes.strip_prefix(REPLAY_MAGIC_V2) {
+            (bytes, 2)
+        } else {
+            (bytes.strip_prefix(REPLAY_MAGIC_V1)?, 1)
+        };
+        let seed = u64::from_le_bytes(bytes.get(0..8)?.try_into().ok()?);
+        let score = u32::from_le_bytes(bytes.get(8..12)?.try_into().ok()?);
+        let (outcome, mut runs) = if version >= 2 {
+            (outcome_from_byte(*bytes.get(12)?)?, bytes.get(13..)?)
+        } else {
+            (None, bytes.get(12..)?)
+        };
+
+        let mut level = None;
+        if version >= 3 {
+            let length = u32::from_le_bytes(runs.get(0..4)?.try_into().ok()?) as usize;
+            let ron = runs.get(4..4 + length)?;
+            if length > 0 {
+                level = Some(ron::de::from_bytes(ron).ok()?);
+            }
+            runs = runs.get(4 + length..)?;
+        }
+
+        if runs.len() % 3 != 0 {
+            return None;
+        }
+        let mut inputs = Vec::new();
+        for run in runs.chunks_exact(3) {
+            let count = u16::from_le_bytes([run[0], run[1]]);
+            inputs.extend(std::iter::repeat_n(run[2] as i8, count as usize));
+        }
+
+        Some(Self {
+            seed,
+            level,
+            score,
+            outcome,
+            inputs,
+        })
+    }
+}
+
+// Inputs of the run being played
+#[derive(Resource, Default)]
+pub struct RunRecording(Vec<i8>);
+
+// The replay being watched, if any, and the next tick to play
+#[derive(Resource, Default)]
+pub struct ActiveReplay {
+    replay: Option<Replay>,
+    tick: usize,
+}
+
+impl ActiveReplay {
+    pub fn start(&mut self, replay: Replay) {
+        self.replay = Some(replay);
+        self.tick = 0;
+    }
+
+    pub fn seed(&self) -> Option<u64> {
+        self.replay.as_ref().map(|replay| replay.seed)
+    }
+
+    pub fn level(&self) -> Option<&HandMadeLevel> {
+        self.replay
+            .as_ref()
+            .and_then(|replay| replay.level.as_ref())
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.replay.is_some()
+    }
+
+    // Whether every input of the replay has been played
+    pub fn is_finished(&self) -> bool {
+        self.replay
+            .as_ref()
+            .is_some_and(|replay| self.tick >= replay.inputs.len())
+    }
+}
+
+fn quantize(input: f32) -> i8 {
+    (input.clamp(-1.0, 1.0) * i8::MAX as f32).round() as i8
+}
+
+fn dequantize(input: i8) -> f32 {
+    (input as f32 / i8::MAX as f32).max(-1.0)
+}
+
+// The move actions steer at full speed. Otherwise touches and the stick steer
+// in proportion to how far they go.
+pub(super) fn read_move_input(
+    mut move_input: ResMut<MoveInput>,
+    actions: Actions,
+    gamepads: Query<&Gamepad>,
+    touches: Res<Touches>,
+    touch_steering: Res<TouchSteering>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+) {
+    move_input.0 = if actions.pressed(Action::MoveRight) {
+        1.0
+    } else if actions.pressed(Action::MoveLeft) {
+        -1.0
+    } else if let Some(touch) = touch_move_input(&touches, *touch_steering, &window_query) {
+        touch
+    } else {
+        gamepads
+            .iter()
+            .map(|gamepad| gamepad.left_stick().x)
+            .find(|x| x.abs() > STICK_DEADZONE)
+            .unwrap_or(0.0)
+    };
+}
+
+// Fingers steering in opposite directions cancel out
+fn touch_move_input(
+    touches: &Touches,
+    touch_steering: TouchSteering,
+    window_query: &Query<&Window, With<PrimaryWindow>>,
+) -> Option<f32> {
+    let window_width = window_query.single().ok()?.width();
+    touches
+        .iter()
+        .map(|touch| {
+            touch_steering.move_input(touch.start_position(), touch.position(), window_width)
+        })
+        .reduce(|sum, input| sum + input)
+        .map(|sum| sum.clamp(-1.0, 1.0))
+}
+
+// Takes the input of this tick from the replay being watched, or records the
+// player's input
+pub(super) fn step_move_input(
+    mut move_input: ResMut<MoveInput>,
+    mut recording: ResMut<RunRecording>,
+    mut active_replay: ResMut<ActiveReplay>,
+) {
+    let tick = active_replay.tick;
+    if let Some(replay) = &active_replay.replay {
+        move_input.0 = dequantize(replay.inputs.get(tick).copied().unwrap_or_default());
+        active_replay.tick += 1;
+        return;
+    }
+
+    // Play with the recorded precision, so the replay matches the run exactly
+    let quantized = quantize(move_input.0);
+    recording.0.push(quantized);
+    move_input.0 = dequantize(quantized);
+}
+
+// The replay of the run that just ended, kept until it is saved. Runs the
+// player quit, practice runs and replays being watched have none.
+#[derive(Resource, Default)]
+pub struct FinishedReplay(pub Option<Replay>);
+
+// Runs at game over, and when leaving the game, as runs that reach the castle
+// leave it right away. The inputs are taken once, so a run is only recorded
+// once.
+pub(super) fn record_replay(
+    mut recording: ResMut<RunRecording>,
+    mut run_finished: MessageReader<RunFinished>,
+    mut finished_replay: ResMut<FinishedReplay>,
+    level_seed: Res<LevelSeed>,
+    run_level: Res<RunLevel>,
+    practice: Res<Practice>,
+) {
+    let inputs = std::mem::take(&mut recording.0);
+    let Some(run) = run_finished
+        .read()
+        .last()
+        .filter(|_| !practice.0 && !inputs.is_empty())
+    else {
+        return;
+    };
+
+    finished_replay.0 = Some(Replay {
+        seed: level_seed.0,
+        level: run_level.0.clone(),
+        score: run.points,
+        outcome: Some(run.outcome),
+        inputs,
+    });
+}
+
+pub(super) fn save_replay(mut finished_replay: ResMut<FinishedReplay>) {
+    let Some(replay) = finished_replay.0.take() else {
+        return;
+    };
+    let bytes = replay.to_bytes();
+
+    write_replay(ReplaySlot::Last, &bytes);
+    if Replay::load(ReplaySlot::Best).is_none_or(|best| best.score < replay.score) {
+        write_replay(ReplaySlot::Best, &bytes);
+    }
+}
+
+pub(super) fn reset_replay(
+    mut recording: ResMut<RunRecording>,
+    mut active_replay: ResMut<ActiveReplay>,
+    mut move_input: ResMut<MoveInput>,
+) {
+    recording.0.clear();
+    active_replay.replay = None;
+    move_input.0 = 0.0;
+}
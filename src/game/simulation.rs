@@ -0,0 +1,362 @@
+use bevy::{prelude::*, state::app::StatesPlugin};
+use rand::{SeedableRng, rngs::StdRng};
+
+use crate::{GameState, SoundEvent, cleanup};
+
+use super::{
+    bob, castle, coin,
+    collision::{self, BobLandedOn, BobTouched},
+    level::{self, GameObject, HandMadeLevel},
+    physics::{self, CameraHeight, Position},
+    platform,
+    replay::{self, ActiveReplay, FinishedReplay, MoveInput, RunRecording},
+    spring, squirrel,
+};
+
+#[derive(Component)]
+pub(super) struct GameEntity;
+
+#[derive(Component)]
+pub(super) struct GameDynamicEntity;
+
+#[derive(Component)]
+pub(super) struct MovingObject {
+    pub(super) width: f32,
+    pub(super) velocity_x: f32,
+    pub(super) dir: f32,
+}
+
+#[derive(Resource, Default)]
+pub struct Points(pub u32);
+
+// Coins collected in the current run
+#[derive(Resource, Default)]
+pub struct Coins(pub u32);
+
+#[derive(Resource, Default)]
+pub struct GameObjects(pub Vec<GameObject>);
+
+// Seed of the current level. Every random roll of a run comes from GameRng,
+// which is seeded with it, so a run can be regenerated from the seed alone.
+#[derive(Resource, Default)]
+pub struct LevelSeed(pub u64);
+
+// Seed given on the command line, used instead of a random one
+#[derive(Resource, Default)]
+pub struct SeedOverride(pub Option<u64>);
+
+// Hand-made level, played instead of a generated one
+#[derive(Resource, Default)]
+pub struct LevelOverride(pub Option<HandMadeLevel>);
+
+// Hand-made level of the current run, None if it was generated from the seed
+#[derive(Resource, Default)]
+pub struct RunLevel(pub Option<HandMadeLevel>);
+
+// Whether the current run is only practice: a playtest from the editor, or a
+// run whose level changed while it was played. Practice runs aren't kept as
+// replays, ghosts or high scores, and aren't submitted to the leaderboard.
+#[derive(Resource, Default)]
+pub struct Practice(pub bool);
+
+// Level to try out in the next run, with Bob dropped at the given height. It
+// is removed once the run starts.
+#[derive(Resource)]
+pub struct Playtest {
+    pub objects: Vec<GameObject>,
+    pub height: f32,
+}
+
+#[derive(Resource)]
+pub struct GameRng(pub(super) StdRng);
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self(StdRng::seed_from_u64(0))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
+pub enum PlayState {
+    #[default]
+    Ready,
+    Running,
+    Paused,
+    GameOver,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum RunOutcome {
+    ReachedCastle,
+    Died,
+}
+
+#[derive(Message)]
+pub struct RunFinished {
+    pub outcome: RunOutcome,
+    pub points: u32,
+}
+
+// Parts of a simulation tick, in the order they run
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SimulationSet {
+    // Decides the input of the tick
+    Input,
+    // Moves Bob and the objects and resolves collisions
+    Step,
+    // Observes the outcome of the tick
+    Record,
+}
+
+// The game logic without any rendering, audio or file access. It only needs
+// MinimalPlugins to run, so it can be driven by tests and tools.
+pub struct SimulationPlugin;
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<StatesPlugin>() {
+            app.add_plugins(StatesPlugin);
+        }
+
+        app.init_state::<GameState>()
+            .init_state::<PlayState>()
+            .add_message::<SoundEvent>()
+            .add_message::<RunFinished>()
+            .add_message::<BobLandedOn>()
+            .add_message::<BobTouched>()
+            .insert_resource(Time::<Fixed>::from_hz(physics::TICKS_PER_SECOND))
+            .init_resource::<Points>()
+            .init_resource::<Coins>()
+            .init_resource::<GameObjects>()
+            .init_resource::<LevelSeed>()
+            .init_resource::<SeedOverride>()
+            .init_resource::<LevelOverride>()
+            .init_resource::<RunLevel>()
+            .init_resource::<Practice>()
+            .init_resource::<GameRng>()
+            .init_resource::<CameraHeight>()
+            .init_resource::<MoveInput>()
+            .init_resource::<RunRecording>()
+            .init_resource::<FinishedReplay>()
+            .init_resource::<ActiveReplay>()
+            .configure_sets(
+                FixedUpdate,
+                (
+                    SimulationSet::Input,
+                    SimulationSet::Step,
+                    SimulationSet::Record,
+                )
+                    .chain()
+                    .run_if(
+                        in_state(GameState::Playing)
+                            .and(in_state(PlayState::Running))
+                            .and(state_settled),
+                    ),
+            )
+            .add_systems(OnEnter(GameState::Playing), setup_play)
+            .add_systems(
+                OnExit(GameState::Playing),
+                (
+                    cleanup::<GameEntity>,
+                    (
+                        replay::record_replay,
+                        (reset_simulation, replay::reset_replay),
+                    )
+                        .chain(),
+                ),
+            )
+            .add_systems(
+                FixedPreUpdate,
+                physics::store_previous_positions.run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                FixedUpdate,
+                replay::step_move_input.in_set(SimulationSet::Input),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    bob::move_bob,
+                    bob::update_bob,
+                    move_objects,
+                    collision::detect_collisions,
+                    platform::land_on_platforms,
+                    spring::land_on_springs,
+                    coin::collect_coins,
+                    squirrel::hit_squirrels,
+                    castle::enter_castle,
+                    bob::check_bob_has_fallen,
+                    platform::pulverize_platforms,
+                    cleanup_objects,
+                )
+                    .chain()
+                    .in_set(SimulationSet::Step),
+            )
+            .add_systems(
+                FixedUpdate,
+                bob::update_bob.run_if(
+                    in_state(GameState::Playing)
+                        .and(in_state(PlayState::GameOver))
+                        .and(state_settled),
+                ),
+            )
+            .add_systems(
+                FixedUpdate,
+                spawn_objects
+                    .after(SimulationSet::Step)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                OnEnter(PlayState::GameOver),
+                (finish_run, replay::record_replay).chain(),
+            );
+    }
+}
+
+// State transitions are applied once per frame, while the fixed timestep may
+// run several ticks in a frame. Stop simulating as soon as a tick requests a
+// transition, so the run plays out the same at any frame rate.
+fn state_settled(
+    next_game_state: Res<NextState<GameState>>,
+    next_play_state: Res<NextState<PlayState>>,
+) -> bool {
+    matches!(*next_game_state, NextState::Unchanged)
+        && matches!(*next_play_state, NextState::Unchanged)
+}
+
+pub(super) fn setup_play(
+    mut commands: Commands,
+    mut game_objects: ResMut<GameObjects>,
+    mut level_seed: ResMut<LevelSeed>,
+    mut game_rng: ResMut<GameRng>,
+    seed_override: Res<SeedOverride>,
+    level_override: Res<LevelOverride>,
+    mut run_level: ResMut<RunLevel>,
+    mut practice: ResMut<Practice>,
+    playtest: Option<Res<Playtest>>,
+    mut camera_height: ResMut<CameraHeight>,
+    active_replay: Res<ActiveReplay>,
+    mut play_state: ResMut<NextState<PlayState>>,
+) {
+    level_seed.0 = active_replay
+        .seed()
+        .or(seed_override.0)
+        .unwrap_or_else(rand::random);
+    game_rng.0 = StdRng::seed_from_u64(level_seed.0);
+    // A replay is played on the level it was recorded on, whatever level is
+    // given on the command line
+    run_level.0 = if active_replay.is_playing() {
+        active_replay.level().cloned()
+    } else {
+        level_override.0.clone()
+    };
+    practice.0 = false;
+
+    let mut bob_height = -240.0 + 32.0;
+    if let Some(playtest) = playtest
+        && !active_replay.is_playing()
+    {
+        game_objects.0 = playtest.objects.clone();
+        camera_height.0 = playtest.height;
+        bob_height = playtest.height;
+        practice.0 = true;
+        commands.remove_resource::<Playtest>();
+    } else {
+        game_objects.0 = match &run_level.0 {
+            Some(level) => level.objects.clone(),
+            None => level::generate_level(&mut game_rng.0),
+        };
+    }
+
+    bob::setup_bob(&mut commands, bob_height);
+
+    // Replays start right away, there is nobody to press ready
+    if active_replay.is_playing() {
+        play_state.set(PlayState::Running);
+    }
+}
+
+fn spawn_objects(
+    mut commands: Commands,
+    mut game_objects: ResMut<GameObjects>,
+    camera_height: Res<CameraHeight>,
+) {
+    let max_y = camera_height.0 + 1.1 * 480.0;
+
+    for object in &mut game_objects.0 {
+        // Only spawn objects that are on screen and a 10% above
+        if object.is_spawned || object.y > max_y {
+            continue;
+        }
+
+        object.is_spawned = true;
+
+        let position = Vec2::new(object.x - 160.0, object.y - 240.0);
+        match object.object_type {
+            level::GameObjectType::Platform(speed) => {
+                platform::spawn_platform(&mut commands, speed, position);
+            }
+            level::GameObjectType::Squirrel => {
+                squirrel::spawn_squirrel(&mut commands, position);
+            }
+            level::GameObjectType::Coin => {
+                coin::spawn_coin(&mut commands, position);
+            }
+            level::GameObjectType::Spring => {
+                spring::spawn_spring(&mut commands, position);
+            }
+            level::GameObjectType::Castle => {
+                castle::spawn_castle(&mut commands, position);
+            }
+        }
+    }
+}
+
+fn cleanup_objects(
+    mut commands: Commands,
+    mut game_objects: ResMut<GameObjects>,
+    mut dynamic_objects: Query<(Entity, &Position), With<GameDynamicEntity>>,
+    camera_height: Res<CameraHeight>,
+) {
+    let min_y = camera_height.0 - 1.2 * 240.0;
+
+    game_objects.0.retain(|o| !o.is_spawned);
+
+    for (entity, position) in &mut dynamic_objects {
+        // Despawn objects that are below screen's bottom
+        if position.y < min_y {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn move_objects(
+    mut objects_query: Query<(&mut MovingObject, &mut Position), With<MovingObject>>,
+    time: Res<Time>,
+) {
+    for (mut obj, mut position) in &mut objects_query {
+        position.x += obj.velocity_x * obj.dir * time.delta_secs();
+
+        if position.x + obj.width / 2.0 > 160.0 {
+            obj.dir = -1.0;
+        } else if position.x - obj.width / 2.0 < -160.0 {
+            obj.dir = 1.0;
+        }
+    }
+}
+
+pub(super) fn finish_run(mut run_finished: MessageWriter<RunFinished>, points: Res<Points>) {
+    run_finished.write(RunFinished {
+        outcome: RunOutcome::Died,
+        points: points.0,
+    });
+}
+
+pub(super) fn reset_simulation(
+    mut camera_height: ResMut<CameraHeight>,
+    mut points: ResMut<Points>,
+    mut coins: ResMut<Coins>,
+) {
+    camera_height.0 = 0.0;
+    points.0 = 0;
+    coins.0 = 0;
+}
@@ -1,25 +1,56 @@
-use super::{GameDynamicEntity, GameEntity};
+use super::{
+    GameDynamicEntity, GameEntity,
+    bob::{BOB_JUMP_VELOCITY, Bob},
+    collision::{BobLandedOn, Collider, CollisionLayer},
+    physics::Position,
+};
+use crate::{Sound, SoundEvent, assets::GameAssets};
 use bevy::prelude::*;
 
 pub const SPRING_HEIGHT: f32 = 0.3 * 32.0;
 pub const SPRING_SIZE: Vec2 = Vec2::new(32.0, 32.0);
+pub const SPRING_JUMP_VELOCITY: f32 = BOB_JUMP_VELOCITY * 1.5;
 
 #[derive(Component, Default)]
 pub struct Spring;
 
-pub(super) fn spawn_spring(
-    commands: &mut Commands,
-    asset_server: &Res<AssetServer>,
-    position: Vec2,
-) {
-    let spring_texture = asset_server.load("sprites/spring.png");
-
+pub(super) fn spawn_spring(commands: &mut Commands, position: Vec2) {
     // Spawn spring
     commands.spawn((
         Spring,
+        Collider::rect(SPRING_SIZE, CollisionLayer::Ground),
         GameEntity,
         GameDynamicEntity,
-        Sprite::from_image(spring_texture),
         Transform::from_xyz(position.x, position.y, 20.0),
+        Position(position),
     ));
 }
+
+pub(super) fn land_on_springs(
+    mut landed: MessageReader<BobLandedOn>,
+    mut bob_query: Query<(&Position, &mut Bob)>,
+    springs_query: Query<(), With<Spring>>,
+    mut sound_events: MessageWriter<SoundEvent>,
+) {
+    let (bob_position, mut bob) = bob_query.single_mut().unwrap();
+    for BobLandedOn(entity) in landed.read() {
+        if !springs_query.contains(*entity) {
+            continue;
+        }
+
+        bob.velocity.y = SPRING_JUMP_VELOCITY;
+        sound_events.write(SoundEvent::at(Sound::Highjump, bob_position.0));
+    }
+}
+
+pub(super) fn add_spring_sprites(
+    mut commands: Commands,
+    spring_query: Query<Entity, Added<Spring>>,
+    game_assets: Res<GameAssets>,
+) {
+    for entity in &spring_query {
+        commands
+            .entity(entity)
+            .insert(Sprite::from_image(game_assets.spring.clone()));
+    }
+}
@@ -1,42 +1,30 @@
-use crate::game::anim::{AnimationIndices, AnimationTimer};
+use crate::{
+    Sound, SoundEvent,
+    assets::GameAssets,
+    game::anim::{AnimationIndices, AnimationTimer},
+};
 
-use super::{GameDynamicEntity, GameEntity, MovingObject};
+use super::{
+    GameDynamicEntity, GameEntity, MovingObject, PlayState,
+    collision::{BobTouched, Collider, CollisionLayer},
+    physics::{Position, PreviousPosition},
+};
 use bevy::prelude::*;
 
 //const SQUIRREL_ANIMATION_SPEED: f32 = 10.0;
 pub const SQUIRREL_HEIGHT: f32 = 0.6 * 32.0;
 pub const SQUIRREL_WIDTH: f32 = 32.0;
 pub const SQUIRREL_SIZE: Vec2 = Vec2::new(SQUIRREL_WIDTH, SQUIRREL_HEIGHT);
-const SQUIRREL_VELOCITY_X: f32 = 60.0;
+pub const SQUIRREL_VELOCITY_X: f32 = 60.0;
 
 #[derive(Component, Default)]
 pub struct Squirrel;
 
-pub(super) fn spawn_squirrel(
-    commands: &mut Commands,
-    asset_server: &Res<AssetServer>,
-    texture_atlases: &mut ResMut<Assets<TextureAtlasLayout>>,
-    position: Vec2,
-) {
-    // Load the squirrel's sprite sheet and create a texture atlas from it
-    let squirrel_texture = asset_server.load("sprites/squirrel.png");
-    let layout_handle = texture_atlases.add(TextureAtlasLayout::from_grid(
-        UVec2::new(32, 32),
-        2,
-        1,
-        None,
-        None,
-    ));
-
-    let animation_indices = AnimationIndices {
-        first: 0,
-        last: 1,
-        ..default()
-    };
-
-    // Spawn coin
+pub(super) fn spawn_squirrel(commands: &mut Commands, position: Vec2) {
+    // Spawn squirrel
     commands.spawn((
         Squirrel,
+        Collider::rect(SQUIRREL_SIZE, CollisionLayer::Touch),
         GameEntity,
         GameDynamicEntity,
         MovingObject {
@@ -44,19 +32,48 @@ pub(super) fn spawn_squirrel(
             velocity_x: SQUIRREL_VELOCITY_X,
             dir: 1.0,
         },
-        Sprite::from_atlas_image(
-            squirrel_texture,
-            TextureAtlas {
-                layout: layout_handle,
-                index: 0,
-            },
-        ),
         Transform::from_xyz(position.x, position.y, 20.0),
-        animation_indices,
-        AnimationTimer(Timer::from_seconds(0.1, TimerMode::Repeating)),
+        Position(position),
+        PreviousPosition(position),
     ));
 }
 
+pub(super) fn hit_squirrels(
+    mut touched: MessageReader<BobTouched>,
+    squirrels_query: Query<&Position, With<Squirrel>>,
+    mut sound_events: MessageWriter<SoundEvent>,
+    mut play_state: ResMut<NextState<PlayState>>,
+) {
+    for BobTouched(entity) in touched.read() {
+        let Ok(squirrel_position) = squirrels_query.get(*entity) else {
+            continue;
+        };
+
+        sound_events.write(SoundEvent::at(Sound::Hit, squirrel_position.0));
+        play_state.set(PlayState::GameOver);
+    }
+}
+
+pub(super) fn add_squirrel_sprites(
+    mut commands: Commands,
+    squirrel_query: Query<Entity, Added<Squirrel>>,
+    game_assets: Res<GameAssets>,
+) {
+    for entity in &squirrel_query {
+        let animation_indices = AnimationIndices {
+            first: 0,
+            last: 1,
+            ..default()
+        };
+
+        commands.entity(entity).insert((
+            game_assets.squirrel_sprite(),
+            animation_indices,
+            AnimationTimer(Timer::from_seconds(0.1, TimerMode::Repeating)),
+        ));
+    }
+}
+
 pub(super) fn animate_squirrels(
     mut squirrels: Query<
         (
@@ -0,0 +1,88 @@
+use std::time::Duration;
+
+use bevy::{ecs::message::Messages, prelude::*, time::TimeUpdateStrategy};
+
+use crate::GameState;
+
+use super::{
+    ActiveReplay, PlayState, Points, Replay, RunFinished, RunOutcome, SimulationPlugin,
+    physics::TICKS_PER_SECOND,
+};
+
+// Updates on top of one per input, to start the run and let its end register
+const EXTRA_UPDATES: usize = 10;
+
+// Score and outcome of a run. The outcome is None if the run didn't end.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RunResult {
+    pub points: u32,
+    pub outcome: Option<RunOutcome>,
+}
+
+// A replay whose claimed result differs from the result of its inputs
+#[derive(Debug)]
+pub struct Rejected {
+    pub claimed: RunResult,
+    pub simulated: RunResult,
+}
+
+// Plays the inputs of a replay in a headless game, one tick per update, with
+// the same systems as the game
+pub fn simulate_replay(replay: &Replay) -> RunResult {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, SimulationPlugin))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+            1.0 / TICKS_PER_SECOND,
+        )));
+    app.update();
+
+    app.world_mut()
+        .resource_mut::<ActiveReplay>()
+        .start(replay.clone());
+    app.world_mut()
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Playing);
+
+    let mut cursor = app.world().resource::<Messages<RunFinished>>().get_cursor();
+    for _ in 0..replay.inputs.len() + EXTRA_UPDATES {
+        app.update();
+
+        let messages = app.world().resource::<Messages<RunFinished>>();
+        if let Some(run) = cursor.read(messages).last() {
+            return RunResult {
+                points: run.points,
+                outcome: Some(run.outcome),
+            };
+        }
+
+        // The player quit after the last input, unless its tick ended the run
+        let world = app.world();
+        if world.resource::<ActiveReplay>().is_finished()
+            && matches!(
+                *world.resource::<NextState<PlayState>>(),
+                NextState::Unchanged
+            )
+        {
+            break;
+        }
+    }
+
+    RunResult {
+        points: app.world().resource::<Points>().0,
+        outcome: None,
+    }
+}
+
+// Checks that the inputs of a replay give the score and outcome it claims
+pub fn verify_replay(replay: &Replay) -> Result<RunResult, Rejected> {
+    let claimed = RunResult {
+        points: replay.score,
+        outcome: replay.outcome,
+    };
+    let simulated = simulate_replay(replay);
+    if simulated == claimed {
+        Ok(simulated)
+    } else {
+        Err(Rejected { claimed, simulated })
+    }
+}
@@ -1,4 +1,9 @@
-use crate::{GameState, cleanup, click_sound};
+use crate::{
+    GameState,
+    assets::{GameAssets, HELP_SCREEN_COUNT},
+    cleanup, click_sound,
+    input::confirm_just_pressed,
+};
 use bevy::prelude::*;
 
 #[derive(Component)]
@@ -16,7 +21,7 @@ impl Plugin for HelpPlugin {
             .add_systems(
                 Update,
                 (
-                    show_next_screen.run_if(in_state(GameState::Help).and(has_user_input)),
+                    show_next_screen.run_if(in_state(GameState::Help).and(confirm_just_pressed)),
                     click_sound.run_if(
                         resource_changed::<HelpScreenIndex>
                             .and(not(resource_added::<HelpScreenIndex>)),
@@ -26,25 +31,25 @@ impl Plugin for HelpPlugin {
     }
 }
 
-fn setup_help(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn setup_help(mut commands: Commands, game_assets: Res<GameAssets>) {
     commands.spawn((
         HelpEntity,
-        Sprite::from_image(asset_server.load("sprites/help1.png")),
+        Sprite::from_image(game_assets.help[0].clone()),
         Transform::from_xyz(0.0, 0.0, 100.0),
     ));
 }
 
 fn show_next_screen(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
+    game_assets: Res<GameAssets>,
     mut help_screen: ResMut<HelpScreenIndex>,
     mut state: ResMut<NextState<GameState>>,
 ) {
     help_screen.0 += 1;
-    if help_screen.0 < 5 {
+    if help_screen.0 < HELP_SCREEN_COUNT {
         commands.spawn((
             HelpEntity,
-            Sprite::from_image(asset_server.load(format!("sprites/help{}.png", help_screen.0 + 1))),
+            Sprite::from_image(game_assets.help[help_screen.0].clone()),
             Transform::from_xyz(0.0, 0.0, 100.0 + (help_screen.0 as f32)),
         ));
     } else {
@@ -52,13 +57,3 @@ fn show_next_screen(
         state.set(GameState::Menu);
     }
 }
-
-pub fn has_user_input(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    mouse_button_input: Res<ButtonInput<MouseButton>>,
-    touch_input: Res<Touches>,
-) -> bool {
-    keyboard_input.just_pressed(KeyCode::Space)
-        || mouse_button_input.just_pressed(MouseButton::Left)
-        || touch_input.any_just_pressed()
-}
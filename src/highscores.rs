@@ -1,14 +1,101 @@
+#![allow(clippy::type_complexity)]
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use crate::{
-    GameState, cleanup, click_sound,
-    settings::{HIGHSCORE_COUNT, read_settings, write_high_scores},
+    GameState,
+    assets::GameAssets,
+    cleanup, click_sound,
+    focus::BackButton,
+    game::level::GRID_SIZE,
+    leaderboard::{Leaderboard, LeaderboardEntry},
+    settings::{HIGHSCORE_COUNT, read_settings, write_high_scores, write_player_name},
+};
+use bevy::{
+    prelude::*,
+    tasks::{Task, futures::check_ready},
 };
-use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
 #[derive(Component)]
 struct HighScoresEntity;
 
+// Container of the rows of the shown tab
+#[derive(Component)]
+struct ScoreRows;
+
+#[derive(Component)]
+enum HighScoresButtonAction {
+    Back,
+    Tab(HighScoresTab),
+}
+
+#[derive(Resource, Default, Clone, Copy, PartialEq)]
+enum HighScoresTab {
+    #[default]
+    Local,
+    Global,
+}
+
+#[derive(Component)]
+struct TabText(HighScoresTab);
+
+// Best runs of the leaderboard, fetched when the global tab is opened
+#[derive(Resource, Default)]
+struct GlobalScores {
+    task: Option<Task<Result<Vec<LeaderboardEntry>, String>>>,
+    scores: Option<Result<Vec<LeaderboardEntry>, String>>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct HighScoreEntry {
+    pub name: String,
+    pub score: u32,
+    // Seconds since the Unix epoch, unknown for scores of older versions
+    pub date: Option<u64>,
+    // Highest point Bob reached, in pixels from the bottom of the level
+    pub height: f32,
+    pub coins: u32,
+    pub seed: u64,
+    // Name of the hand-made level of the run, None for a generated level
+    #[serde(default)]
+    pub level: Option<String>,
+}
+
+impl HighScoreEntry {
+    // Entry of a run that just finished, still to be named
+    pub fn new(score: u32, height: f32, coins: u32, seed: u64, level: Option<String>) -> Self {
+        Self {
+            name: String::new(),
+            score,
+            date: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .ok()
+                .map(|duration| duration.as_secs()),
+            height,
+            coins,
+            seed,
+            level,
+        }
+    }
+
+    // Entry with nothing but a name and a score
+    pub fn unnamed(name: &str, score: u32) -> Self {
+        Self {
+            name: name.to_string(),
+            score,
+            date: None,
+            height: 0.0,
+            coins: 0,
+            seed: 0,
+            level: None,
+        }
+    }
+}
+
+// Best runs, highest score first
 #[derive(Resource)]
-pub struct HighScores([u32; HIGHSCORE_COUNT]);
+pub struct HighScores(Vec<HighScoreEntry>);
 
 impl Default for HighScores {
     fn default() -> Self {
@@ -16,26 +103,89 @@ impl Default for HighScores {
     }
 }
 
+impl HighScores {
+    pub fn entries(&self) -> &[HighScoreEntry] {
+        &self.0
+    }
+
+    pub fn qualifies(&self, score: u32) -> bool {
+        self.0.len() < HIGHSCORE_COUNT || self.0.iter().any(|entry| entry.score < score)
+    }
+
+    pub fn insert(&mut self, entry: HighScoreEntry) {
+        let index = self
+            .0
+            .iter()
+            .position(|other| other.score < entry.score)
+            .unwrap_or(self.0.len());
+        self.0.insert(index, entry);
+        self.0.truncate(HIGHSCORE_COUNT);
+    }
+}
+
+// A run that made it into the high scores, waiting for the player's name
+#[derive(Resource, Default)]
+pub struct PendingHighScore(pub Option<HighScoreEntry>);
+
+// Name of runs the player didn't name
+pub const DEFAULT_NAME: &str = "BOB";
+
+// Name the player entered last, which runs that don't make the high scores are
+// submitted to the leaderboard under
+#[derive(Resource)]
+pub struct PlayerName(pub String);
+
+impl Default for PlayerName {
+    fn default() -> Self {
+        Self(
+            read_settings()
+                .player_name
+                .unwrap_or_else(|| DEFAULT_NAME.to_string()),
+        )
+    }
+}
+
 const TRANSPARENT: Color = Color::linear_rgba(0.0, 0.0, 0.0, 0.0);
+const INACTIVE_TAB: Color = Color::srgb(0.5, 0.5, 0.5);
 
 pub struct HighScoresPlugin;
 impl Plugin for HighScoresPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<HighScores>()
+            .init_resource::<PendingHighScore>()
+            .init_resource::<PlayerName>()
+            .init_resource::<Leaderboard>()
+            .init_resource::<HighScoresTab>()
+            .init_resource::<GlobalScores>()
             .add_systems(OnEnter(GameState::HighScores), setup_highscores)
             .add_systems(
                 OnExit(GameState::HighScores),
                 (click_sound, cleanup::<HighScoresEntity>),
             )
-            .add_systems(Update, ui_action.run_if(in_state(GameState::HighScores)));
+            .add_systems(
+                Update,
+                (
+                    ui_action,
+                    poll_global_scores,
+                    show_rows.run_if(
+                        resource_changed::<HighScoresTab>.or(resource_changed::<GlobalScores>),
+                    ),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::HighScores)),
+            );
     }
 }
 
 fn setup_highscores(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    high_scores: Res<HighScores>,
+    game_assets: Res<GameAssets>,
+    leaderboard: Res<Leaderboard>,
+    mut tab: ResMut<HighScoresTab>,
 ) {
+    *tab = HighScoresTab::Local;
+    let font = game_assets.font.clone();
+
     commands
         .spawn((
             Node {
@@ -52,7 +202,7 @@ fn setup_highscores(
             parent.spawn((
                 Text::new("HIGHSCORES"),
                 TextFont {
-                    font: asset_server.load("fonts/Retroville NC.ttf"),
+                    font: font.clone(),
                     font_size: 40.0,
                     ..default()
                 },
@@ -60,26 +210,52 @@ fn setup_highscores(
                 TextLayout::new_with_justify(Justify::Center),
             ));
 
-            parent
-                .spawn(Node {
+            if leaderboard.is_enabled() {
+                parent
+                    .spawn(Node {
+                        column_gap: Val::Px(20.0),
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        for (tab, text) in [
+                            (HighScoresTab::Local, "LOCAL"),
+                            (HighScoresTab::Global, "GLOBAL"),
+                        ] {
+                            parent
+                                .spawn((
+                                    Button,
+                                    Node {
+                                        height: Val::Px(40.0),
+                                        align_items: AlignItems::Center,
+                                        ..default()
+                                    },
+                                    BackgroundColor(TRANSPARENT),
+                                    HighScoresButtonAction::Tab(tab),
+                                ))
+                                .with_children(|parent| {
+                                    parent.spawn((
+                                        Text::new(text),
+                                        TextFont {
+                                            font: font.clone(),
+                                            font_size: 24.0,
+                                            ..default()
+                                        },
+                                        TextColor(Color::WHITE),
+                                        TabText(tab),
+                                    ));
+                                });
+                        }
+                    });
+            }
+
+            parent.spawn((
+                Node {
                     flex_direction: FlexDirection::Column,
                     align_items: AlignItems::FlexStart,
                     ..default()
-                })
-                .with_children(|parent| {
-                    for (i, score) in high_scores.0.into_iter().enumerate() {
-                        parent.spawn((
-                            Text::new(format!("{}. {}", i + 1, score)),
-                            TextFont {
-                                font: asset_server.load("fonts/Retroville NC.ttf"),
-                                font_size: 30.0,
-                                ..default()
-                            },
-                            TextColor(Color::WHITE),
-                            TextLayout::new_with_justify(Justify::Center),
-                        ));
-                    }
-                });
+                },
+                ScoreRows,
+            ));
 
             parent
                 .spawn((
@@ -94,39 +270,238 @@ fn setup_highscores(
                         ..default()
                     },
                     BackgroundColor(TRANSPARENT),
+                    HighScoresButtonAction::Back,
+                    BackButton,
                 ))
                 .with_children(|parent| {
-                    let icon = asset_server.load("sprites/back.png");
+                    let icon = game_assets.back.clone();
                     parent.spawn((ImageNode::new(icon),));
                 });
         });
 }
 
 fn ui_action(
-    interaction_query: Query<&Interaction, (Changed<Interaction>, With<Button>)>,
+    interaction_query: Query<
+        (&Interaction, &HighScoresButtonAction),
+        (Changed<Interaction>, With<Button>),
+    >,
     mut game_state: ResMut<NextState<GameState>>,
+    mut tab: ResMut<HighScoresTab>,
+    mut global_scores: ResMut<GlobalScores>,
+    leaderboard: Res<Leaderboard>,
 ) {
-    for interaction in &interaction_query {
-        if *interaction == Interaction::Pressed {
-            game_state.set(GameState::Menu);
+    for (interaction, action) in &interaction_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match action {
+            HighScoresButtonAction::Back => game_state.set(GameState::Menu),
+            HighScoresButtonAction::Tab(HighScoresTab::Global) => {
+                *tab = HighScoresTab::Global;
+                global_scores.scores = None;
+                global_scores.task = leaderboard.fetch();
+            }
+            HighScoresButtonAction::Tab(HighScoresTab::Local) => *tab = HighScoresTab::Local,
         }
     }
 }
 
-pub fn check_and_update_highscores(high_scores: &mut ResMut<HighScores>, score: u32) -> bool {
-    let mut is_highscore = false;
-    let mut prev_score: u32 = 0;
-    for (i, current_score) in high_scores.0.into_iter().enumerate() {
-        if is_highscore {
-            std::mem::swap(&mut high_scores.0[i], &mut prev_score);
-        } else if current_score < score {
-            is_highscore = true;
-            prev_score = current_score;
-            high_scores.0[i] = score;
-        }
+fn poll_global_scores(mut global_scores: ResMut<GlobalScores>) {
+    // Only a finished fetch changes what is shown
+    let Some(task) = global_scores.bypass_change_detection().task.as_mut() else {
+        return;
+    };
+    if let Some(scores) = check_ready(task) {
+        global_scores.task = None;
+        global_scores.scores = Some(scores);
+    }
+}
+
+fn show_rows(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    rows: Query<Entity, With<ScoreRows>>,
+    mut tab_texts: Query<(&TabText, &mut TextColor)>,
+    tab: Res<HighScoresTab>,
+    high_scores: Res<HighScores>,
+    global_scores: Res<GlobalScores>,
+) {
+    for (tab_text, mut color) in &mut tab_texts {
+        color.0 = if tab_text.0 == *tab {
+            Color::WHITE
+        } else {
+            INACTIVE_TAB
+        };
+    }
+
+    let Ok(rows) = rows.single() else {
+        return;
+    };
+    let font = game_assets.font.clone();
+    commands
+        .entity(rows)
+        .despawn_related::<Children>()
+        .with_children(|parent| match *tab {
+            HighScoresTab::Local => {
+                for (i, entry) in high_scores.0.iter().enumerate() {
+                    let details = entry.date.map(|date| {
+                        format!(
+                            "{} {}M {} COINS {}",
+                            format_date(date),
+                            (entry.height / GRID_SIZE) as u32,
+                            entry.coins,
+                            level_details(entry.seed, entry.level.as_deref())
+                        )
+                    });
+                    spawn_row(parent, &font, i, &entry.name, entry.score, details);
+                }
+            }
+            HighScoresTab::Global => match &global_scores.scores {
+                None => spawn_message(parent, &font, "LOADING..."),
+                Some(Err(err)) => {
+                    warn!("Couldn't fetch the leaderboard: {}", err);
+                    spawn_message(parent, &font, "OFFLINE");
+                }
+                Some(Ok(scores)) if scores.is_empty() => {
+                    spawn_message(parent, &font, "NO SCORES YET")
+                }
+                Some(Ok(scores)) => {
+                    for (i, entry) in scores.iter().enumerate() {
+                        let level = level_details(entry.seed, entry.level.as_deref());
+                        let details = match entry.date {
+                            Some(date) => format!("{} {}", format_date(date), level),
+                            None => level,
+                        };
+                        spawn_row(parent, &font, i, &entry.name, entry.score, Some(details));
+                    }
+                }
+            },
+        });
+}
+
+// The level a run was played on
+fn level_details(seed: u64, level: Option<&str>) -> String {
+    match level {
+        Some(name) => format!("LEVEL {}", name),
+        None => format!("SEED {}", seed),
     }
+}
+
+// A "N. NAME SCORE" line, with the details of the run below it
+fn spawn_row(
+    parent: &mut ChildSpawnerCommands,
+    font: &Handle<Font>,
+    index: usize,
+    name: &str,
+    score: u32,
+    details: Option<String>,
+) {
+    parent.spawn((
+        Text::new(format!("{}. {} {}", index + 1, name, score)),
+        TextFont {
+            font: font.clone(),
+            font_size: 24.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        TextLayout::new_with_justify(Justify::Left),
+    ));
+
+    if let Some(details) = details {
+        parent.spawn((
+            Text::new(details),
+            TextFont {
+                font: font.clone(),
+                font_size: 12.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+            TextLayout::new_with_justify(Justify::Left),
+        ));
+    }
+}
+
+fn spawn_message(parent: &mut ChildSpawnerCommands, font: &Handle<Font>, message: &str) {
+    parent.spawn((
+        Text::new(message),
+        TextFont {
+            font: font.clone(),
+            font_size: 24.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+    ));
+}
+
+// Keeps a run that made it into the high scores until the player names it.
+// Any other run is submitted to the leaderboard right away, under the name the
+// player entered last. Returns whether the run made it.
+pub fn check_new_highscore(
+    high_scores: &HighScores,
+    pending: &mut PendingHighScore,
+    leaderboard: &Leaderboard,
+    player_name: &PlayerName,
+    mut entry: HighScoreEntry,
+) -> bool {
+    let is_highscore = high_scores.qualifies(entry.score);
     if is_highscore {
-        write_high_scores(high_scores.0);
+        pending.0 = Some(entry);
+    } else {
+        entry.name = player_name.0.clone();
+        leaderboard.submit(LeaderboardEntry::from(&entry));
     }
     is_highscore
 }
+
+// Adds the pending run to the high scores, under the given name, and submits
+// it to the leaderboard. The name is kept for the runs that follow.
+pub fn save_pending_highscore(
+    high_scores: &mut HighScores,
+    pending: &mut PendingHighScore,
+    player_name: &mut PlayerName,
+    leaderboard: &Leaderboard,
+    name: String,
+) {
+    if let Some(mut entry) = pending.0.take() {
+        entry.name = name;
+        leaderboard.submit(LeaderboardEntry::from(&entry));
+        if player_name.0 != entry.name {
+            player_name.0 = entry.name.clone();
+            write_player_name(&player_name.0);
+        }
+        high_scores.insert(entry);
+        write_high_scores(&high_scores.0);
+    }
+}
+
+// Where to go once a run is over, the name entry screen after a new record
+pub fn after_run_state(pending: &PendingHighScore) -> GameState {
+    if pending.0.is_some() {
+        GameState::NameEntry
+    } else {
+        GameState::Menu
+    }
+}
+
+// Formats a Unix timestamp as a YYYY-MM-DD date
+fn format_date(timestamp: u64) -> String {
+    // Converts days since the epoch to a civil date, see
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let days = (timestamp / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
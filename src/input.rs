@@ -0,0 +1,272 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::settings::read_settings;
+
+// What the player can do, whatever the device
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    Confirm,
+    Back,
+    Pause,
+}
+
+impl Action {
+    pub const ALL: [Action; 5] = [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Confirm,
+        Action::Back,
+        Action::Pause,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::MoveLeft => "LEFT",
+            Action::MoveRight => "RIGHT",
+            Action::Confirm => "CONFIRM",
+            Action::Back => "BACK",
+            Action::Pause => "PAUSE",
+        }
+    }
+}
+
+// One input that triggers an action
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    // Any touch of the screen
+    Touch,
+    Gamepad(GamepadButton),
+}
+
+impl Binding {
+    // Mouse clicks and touches act on what they point at
+    pub fn is_pointer(self) -> bool {
+        matches!(self, Binding::Mouse(_) | Binding::Touch)
+    }
+
+    // Whether both bindings are of the same device
+    pub fn same_device(self, other: Binding) -> bool {
+        std::mem::discriminant(&self) == std::mem::discriminant(&other)
+    }
+
+    // Upper case, which is all the game's font has
+    pub fn name(self) -> String {
+        let name = match self {
+            Binding::Key(key) => {
+                let name = format!("{:?}", key);
+                match name.strip_prefix("Key").or(name.strip_prefix("Digit")) {
+                    Some(name) => name.to_string(),
+                    None => name,
+                }
+            }
+            Binding::Mouse(button) => format!("MOUSE {:?}", button),
+            Binding::Touch => "TOUCH".to_string(),
+            Binding::Gamepad(button) => format!("PAD {:?}", button),
+        };
+        name.to_uppercase()
+    }
+}
+
+// Horizontal distance a finger has to be dragged to steer at full speed
+const DRAG_FULL_SPEED: f32 = 40.0;
+// How touches steer Bob. The original game was also steered by tilting the
+// device, which isn't supported yet, as there is no accelerometer to read.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TouchSteering {
+    // Holding the left or right half of the screen
+    #[default]
+    Halves,
+    // Dragging left or right of where the finger went down
+    Drag,
+}
+
+impl TouchSteering {
+    pub fn name(self) -> &'static str {
+        match self {
+            TouchSteering::Halves => "HALVES",
+            TouchSteering::Drag => "DRAG",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            TouchSteering::Halves => TouchSteering::Drag,
+            TouchSteering::Drag => TouchSteering::Halves,
+        }
+    }
+
+    // Steering of a finger that went down at start and is now at position,
+    // from -1.0 (left) to 1.0 (right)
+    pub fn move_input(self, start: Vec2, position: Vec2, window_width: f32) -> f32 {
+        match self {
+            TouchSteering::Halves if position.x < window_width / 2.0 => -1.0,
+            TouchSteering::Halves => 1.0,
+            TouchSteering::Drag => ((position.x - start.x) / DRAG_FULL_SPEED).clamp(-1.0, 1.0),
+        }
+    }
+}
+
+// The inputs of every action. Missing actions keep their default bindings.
+#[derive(Resource, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Bindings {
+    move_left: Vec<Binding>,
+    move_right: Vec<Binding>,
+    confirm: Vec<Binding>,
+    back: Vec<Binding>,
+    pause: Vec<Binding>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Self {
+            move_left: vec![
+                Binding::Key(KeyCode::ArrowLeft),
+                Binding::Key(KeyCode::KeyA),
+                Binding::Gamepad(GamepadButton::DPadLeft),
+            ],
+            move_right: vec![
+                Binding::Key(KeyCode::ArrowRight),
+                Binding::Key(KeyCode::KeyD),
+                Binding::Gamepad(GamepadButton::DPadRight),
+            ],
+            confirm: vec![
+                Binding::Key(KeyCode::Space),
+                Binding::Key(KeyCode::Enter),
+                Binding::Mouse(MouseButton::Left),
+                Binding::Touch,
+                Binding::Gamepad(GamepadButton::South),
+            ],
+            back: vec![
+                Binding::Key(KeyCode::Escape),
+                Binding::Gamepad(GamepadButton::East),
+            ],
+            // Back also pauses while running, so Escape needn't be bound twice
+            pause: vec![
+                Binding::Key(KeyCode::KeyP),
+                Binding::Gamepad(GamepadButton::Start),
+            ],
+        }
+    }
+}
+
+impl Bindings {
+    pub fn get(&self, action: Action) -> &[Binding] {
+        match action {
+            Action::MoveLeft => &self.move_left,
+            Action::MoveRight => &self.move_right,
+            Action::Confirm => &self.confirm,
+            Action::Back => &self.back,
+            Action::Pause => &self.pause,
+        }
+    }
+
+    fn get_mut(&mut self, action: Action) -> &mut Vec<Binding> {
+        match action {
+            Action::MoveLeft => &mut self.move_left,
+            Action::MoveRight => &mut self.move_right,
+            Action::Confirm => &mut self.confirm,
+            Action::Back => &mut self.back,
+            Action::Pause => &mut self.pause,
+        }
+    }
+
+    // Binds the action to the input, instead of its other inputs of the same
+    // device. An action the input was bound to gets those inputs in exchange,
+    // so no input triggers two actions.
+    pub fn rebind(&mut self, action: Action, binding: Binding) {
+        let bindings = self.get_mut(action);
+        let replaced: Vec<Binding> = bindings
+            .iter()
+            .copied()
+            .filter(|&other| other.same_device(binding) && other != binding)
+            .collect();
+        bindings.retain(|other| !other.same_device(binding));
+        bindings.push(binding);
+
+        for other_action in Action::ALL {
+            let others = self.get_mut(other_action);
+            if other_action == action || !others.contains(&binding) {
+                continue;
+            }
+            others.retain(|&other| other != binding);
+            for &other in &replaced {
+                if !others.contains(&other) {
+                    others.push(other);
+                }
+            }
+        }
+    }
+}
+
+pub struct InputPlugin;
+impl Plugin for InputPlugin {
+    fn build(&self, app: &mut App) {
+        let settings = read_settings();
+        app.insert_resource(settings.bindings)
+            .insert_resource(settings.touch_steering);
+    }
+}
+
+// The state of the actions, from all the devices bound to them
+#[derive(SystemParam)]
+pub struct Actions<'w, 's> {
+    bindings: Res<'w, Bindings>,
+    keyboard_input: Res<'w, ButtonInput<KeyCode>>,
+    mouse_button_input: Res<'w, ButtonInput<MouseButton>>,
+    touch_input: Res<'w, Touches>,
+    gamepads: Query<'w, 's, &'static Gamepad>,
+}
+
+impl Actions<'_, '_> {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.bindings
+            .get(action)
+            .iter()
+            .any(|&binding| match binding {
+                Binding::Key(key) => self.keyboard_input.pressed(key),
+                Binding::Mouse(button) => self.mouse_button_input.pressed(button),
+                Binding::Touch => self.touch_input.iter().next().is_some(),
+                Binding::Gamepad(button) => {
+                    self.gamepads.iter().any(|gamepad| gamepad.pressed(button))
+                }
+            })
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.bindings
+            .get(action)
+            .iter()
+            .any(|&binding| self.binding_just_pressed(binding))
+    }
+
+    // Ignores clicks and touches, for actions on the focused button
+    pub fn just_pressed_without_pointer(&self, action: Action) -> bool {
+        self.bindings
+            .get(action)
+            .iter()
+            .any(|&binding| !binding.is_pointer() && self.binding_just_pressed(binding))
+    }
+
+    fn binding_just_pressed(&self, binding: Binding) -> bool {
+        match binding {
+            Binding::Key(key) => self.keyboard_input.just_pressed(key),
+            Binding::Mouse(button) => self.mouse_button_input.just_pressed(button),
+            Binding::Touch => self.touch_input.any_just_pressed(),
+            Binding::Gamepad(button) => self
+                .gamepads
+                .iter()
+                .any(|gamepad| gamepad.just_pressed(button)),
+        }
+    }
+}
+
+// Run condition of the screens that go on with any confirm input
+pub fn confirm_just_pressed(actions: Actions) -> bool {
+    actions.just_pressed(Action::Confirm)
+}
@@ -0,0 +1,182 @@
+use std::{error::Error, fs, io::ErrorKind, path::Path};
+
+use bevy::{
+    prelude::*,
+    tasks::{IoTaskPool, Task},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{highscores::HighScoreEntry, settings::read_settings};
+
+// Number of runs shown in the global high scores
+pub const LEADERBOARD_COUNT: usize = 10;
+// Runs a leaderboard server keeps, the rest can never be shown
+pub const STORED_COUNT: usize = 100;
+// Longest name of a run, as typed in the game and accepted by the server
+pub const MAX_NAME_LENGTH: usize = 8;
+// Path of the scores on the leaderboard server
+pub const SCORES_PATH: &str = "/scores";
+#[cfg(feature = "leaderboard")]
+const TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+// A finished run, as sent to and received from the leaderboard server
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub name: String,
+    pub score: u32,
+    pub date: Option<u64>,
+    pub seed: u64,
+    // Name of the hand-made level of the run, None for a generated level
+    #[serde(default)]
+    pub level: Option<String>,
+}
+
+impl From<&HighScoreEntry> for LeaderboardEntry {
+    fn from(entry: &HighScoreEntry) -> Self {
+        Self {
+            name: entry.name.clone(),
+            score: entry.score,
+            date: entry.date,
+            seed: entry.seed,
+            level: entry.level.clone(),
+        }
+    }
+}
+
+// The best runs submitted to a leaderboard server, highest score first
+#[derive(Default, Serialize, Deserialize)]
+pub struct LeaderboardStore {
+    entries: Vec<LeaderboardEntry>,
+}
+
+impl LeaderboardStore {
+    // Reads the scores file, a missing file is an empty leaderboard
+    pub fn read(path: &Path) -> Result<Self, Box<dyn Error>> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Ok(ron::from_str(&contents)?),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    // Writes a temporary file and renames it, like the settings
+    pub fn write(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let temp_path = path.with_extension("tmp");
+        fs::write(&temp_path, ron::to_string(self)?)?;
+        fs::rename(&temp_path, path)?;
+        Ok(())
+    }
+
+    // Runs with the same score keep the order they were submitted in. Only
+    // the best STORED_COUNT runs are kept.
+    pub fn insert(&mut self, entry: LeaderboardEntry) {
+        let index = self
+            .entries
+            .iter()
+            .position(|other| other.score < entry.score)
+            .unwrap_or(self.entries.len());
+        self.entries.insert(index, entry);
+        self.entries.truncate(STORED_COUNT);
+    }
+
+    pub fn top(&self, count: usize) -> &[LeaderboardEntry] {
+        &self.entries[..count.min(self.entries.len())]
+    }
+}
+
+#[cfg(feature = "leaderboard")]
+fn agent() -> ureq::Agent {
+    ureq::Agent::config_builder()
+        .timeout_global(Some(TIMEOUT))
+        .build()
+        .into()
+}
+
+#[cfg(feature = "leaderboard")]
+pub fn submit_score(
+    url: &str,
+    entry: &LeaderboardEntry,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    agent()
+        .post(format!("{}{}", url, SCORES_PATH))
+        .content_type("application/ron")
+        .send(ron::to_string(entry)?)?;
+    Ok(())
+}
+
+#[cfg(feature = "leaderboard")]
+pub fn fetch_scores(
+    url: &str,
+    count: usize,
+) -> Result<Vec<LeaderboardEntry>, Box<dyn Error + Send + Sync>> {
+    let contents = agent()
+        .get(format!("{}{}", url, SCORES_PATH))
+        .query("limit", count.to_string())
+        .call()?
+        .body_mut()
+        .read_to_string()?;
+    Ok(ron::from_str(&contents)?)
+}
+
+// Without the leaderboard feature, there is no way to reach a server
+#[cfg(not(feature = "leaderboard"))]
+pub fn submit_score(
+    _url: &str,
+    _entry: &LeaderboardEntry,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    Err("built without the leaderboard feature".into())
+}
+
+#[cfg(not(feature = "leaderboard"))]
+pub fn fetch_scores(
+    _url: &str,
+    _count: usize,
+) -> Result<Vec<LeaderboardEntry>, Box<dyn Error + Send + Sync>> {
+    Err("built without the leaderboard feature".into())
+}
+
+// The leaderboard server runs are shared with, if one is set in the settings
+// and the game is built with the leaderboard feature
+#[derive(Resource)]
+pub struct Leaderboard {
+    url: Option<String>,
+}
+
+impl Default for Leaderboard {
+    fn default() -> Self {
+        Self {
+            url: read_settings()
+                .leaderboard_url
+                .filter(|_| cfg!(feature = "leaderboard"))
+                .map(|url| url.trim_end_matches('/').to_string()),
+        }
+    }
+}
+
+impl Leaderboard {
+    pub fn is_enabled(&self) -> bool {
+        self.url.is_some()
+    }
+
+    // Sends the run in the background. The game goes on without the
+    // leaderboard when the server can't be reached.
+    pub fn submit(&self, entry: LeaderboardEntry) {
+        if let Some(url) = self.url.clone() {
+            IoTaskPool::get()
+                .spawn(async move {
+                    if let Err(err) = submit_score(&url, &entry) {
+                        warn!("Couldn't submit the score to the leaderboard: {}", err);
+                    }
+                })
+                .detach();
+        }
+    }
+
+    // Fetches the best runs in the background
+    pub fn fetch(&self) -> Option<Task<Result<Vec<LeaderboardEntry>, String>>> {
+        let url = self.url.clone()?;
+        Some(IoTaskPool::get().spawn(async move {
+            fetch_scores(&url, LEADERBOARD_COUNT).map_err(|err| err.to_string())
+        }))
+    }
+}
@@ -0,0 +1,415 @@
+use std::collections::HashMap;
+
+use assets::{AudioHandles, GameAssets};
+use bevy::camera::ScalingMode;
+use bevy::{audio::Volume, prelude::*};
+use game::level::WORLD_WIDTH;
+use serde::{Deserialize, Serialize};
+use settings::read_settings;
+
+pub mod assets;
+pub mod controls;
+pub mod focus;
+pub mod game;
+pub mod help;
+pub mod highscores;
+pub mod input;
+pub mod leaderboard;
+pub mod loading;
+pub mod menu;
+pub mod music;
+pub mod nameentry;
+pub mod options;
+pub mod settings;
+pub mod winscreen;
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
+pub enum GameState {
+    #[default]
+    Loading,
+    Menu,
+    Playing,
+    WinScreen,
+    Help,
+    HighScores,
+    NameEntry,
+    Editor,
+    Controls,
+    Options,
+}
+
+// Sound effect being played, despawned once it has finished
+#[derive(Component, Clone, Copy)]
+pub struct SoundEffect {
+    priority: u8,
+    // Real time it started at, in seconds
+    started: f64,
+}
+
+#[derive(Component)]
+pub struct Background;
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Sound {
+    #[default]
+    Click,
+    Coin,
+    Jump,
+    Highjump,
+    Hit,
+}
+
+impl Sound {
+    // Seconds before the sound can play again, so sounds of the same moment,
+    // like several coins picked up at once, don't stack
+    fn cooldown(self) -> f64 {
+        match self {
+            Sound::Click => 0.05,
+            Sound::Coin => 0.08,
+            Sound::Jump | Sound::Highjump | Sound::Hit => 0.1,
+        }
+    }
+
+    // Sounds of higher priority take the place of lower ones when every voice
+    // is in use
+    fn priority(self) -> u8 {
+        match self {
+            Sound::Click => 0,
+            Sound::Coin => 1,
+            Sound::Jump => 2,
+            Sound::Highjump => 3,
+            Sound::Hit => 4,
+        }
+    }
+}
+
+// A sound to play, at a position in the world to pan it by, like the coin
+// picked up
+#[derive(Message, Default, Clone, Copy)]
+pub struct SoundEvent {
+    pub sound: Sound,
+    pub position: Option<Vec2>,
+}
+
+impl SoundEvent {
+    pub fn new(sound: Sound) -> Self {
+        Self {
+            sound,
+            position: None,
+        }
+    }
+
+    pub fn at(sound: Sound, position: Vec2) -> Self {
+        Self {
+            sound,
+            position: Some(position),
+        }
+    }
+}
+
+// Sound effects that can play at once
+const MAX_VOICES: usize = 8;
+
+// When each sound last started
+#[derive(Resource, Default)]
+pub struct SoundCooldowns(HashMap<Sound, f64>);
+
+impl SoundCooldowns {
+    // Whether the sound can play at the given time, in seconds. If it can,
+    // its cooldown starts.
+    pub fn try_play(&mut self, sound: Sound, now: f64) -> bool {
+        if self
+            .0
+            .get(&sound)
+            .is_some_and(|last| now - last < sound.cooldown())
+        {
+            return false;
+        }
+        self.0.insert(sound, now);
+        true
+    }
+}
+
+#[derive(Resource)]
+pub struct SoundEnabled(bool);
+
+impl Default for SoundEnabled {
+    fn default() -> Self {
+        Self(read_settings().sound_enabled)
+    }
+}
+
+// Volume of the music when every volume setting is full, so it stays behind
+// the effects
+const MUSIC_VOLUME: f32 = 0.1;
+// Volume settings change in steps of this size
+const VOLUME_STEP: f32 = 0.1;
+// Distance between the ears of the listener of panned effects, small enough
+// for the sound not to fade with the distance to an ear
+const EAR_GAP: f32 = 0.5;
+// Panned effects play at 3/4 of their volume when centered, which this makes
+// up for
+const PANNING_GAIN: f32 = 4.0 / 3.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VolumeChannel {
+    Master,
+    Music,
+    Effects,
+}
+
+impl VolumeChannel {
+    pub const ALL: [VolumeChannel; 3] = [
+        VolumeChannel::Master,
+        VolumeChannel::Music,
+        VolumeChannel::Effects,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            VolumeChannel::Master => "MASTER",
+            VolumeChannel::Music => "MUSIC",
+            VolumeChannel::Effects => "EFFECTS",
+        }
+    }
+}
+
+// Volume settings, from 0.0 (silent) to 1.0 (full). The master volume scales
+// both the music and the effects.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Volumes {
+    pub master: f32,
+    pub music: f32,
+    pub effects: f32,
+}
+
+impl Default for Volumes {
+    fn default() -> Self {
+        Self {
+            master: 1.0,
+            music: 1.0,
+            effects: 1.0,
+        }
+    }
+}
+
+impl Volumes {
+    pub fn get(&self, channel: VolumeChannel) -> f32 {
+        match channel {
+            VolumeChannel::Master => self.master,
+            VolumeChannel::Music => self.music,
+            VolumeChannel::Effects => self.effects,
+        }
+    }
+
+    // Turns the channel up or down by whole steps, within 0.0 and 1.0
+    pub fn adjust(&mut self, channel: VolumeChannel, steps: i32) {
+        let volume = match channel {
+            VolumeChannel::Master => &mut self.master,
+            VolumeChannel::Music => &mut self.music,
+            VolumeChannel::Effects => &mut self.effects,
+        };
+        let step = (*volume / VOLUME_STEP).round() as i32 + steps;
+        *volume = (step as f32 * VOLUME_STEP).clamp(0.0, 1.0);
+    }
+
+    pub fn music_volume(&self) -> Volume {
+        Volume::Linear(MUSIC_VOLUME * self.master * self.music)
+    }
+
+    pub fn effects_volume(&self) -> Volume {
+        Volume::Linear(PANNING_GAIN * self.master * self.effects)
+    }
+}
+
+// Menus, rendering and audio of the game, on top of the game simulation
+pub struct SuperJumperPlugin;
+impl Plugin for SuperJumperPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SoundEnabled>()
+            .init_resource::<SoundCooldowns>()
+            .insert_resource(read_settings().volumes)
+            .init_state::<GameState>()
+            .add_message::<SoundEvent>()
+            .add_systems(Startup, scene_setup)
+            .add_systems(
+                Update,
+                (
+                    handle_sound_event,
+                    apply_volumes
+                        .run_if(resource_changed::<SoundEnabled>.or(resource_changed::<Volumes>)),
+                ),
+            )
+            .add_plugins((
+                assets::AssetsPlugin,
+                loading::LoadingPlugin,
+                input::InputPlugin,
+                focus::FocusPlugin,
+                controls::ControlsPlugin,
+                options::OptionsPlugin,
+                music::MusicPlugin,
+                menu::MenuPlugin,
+                help::HelpPlugin,
+                game::GamePlugin,
+                highscores::HighScoresPlugin,
+                nameentry::NameEntryPlugin,
+                winscreen::WinScreenPlugin,
+            ));
+    }
+}
+
+fn scene_setup(mut commands: Commands, game_assets: Res<GameAssets>) {
+    // Spawn a 2D camera
+
+    // game_2d_camera.projection.scaling_mode = ScalingMode::FixedVertical(480.0);
+    // game_2d_camera.transform = Transform::from_xyz(0.0, 0.0, 0.0);
+    commands.spawn((
+        Camera2d,
+        Transform::from_xyz(0.0, 0.0, 0.0),
+        Projection::Orthographic(OrthographicProjection {
+            scaling_mode: ScalingMode::FixedVertical {
+                viewport_height: 480.0,
+            },
+            ..OrthographicProjection::default_2d()
+        }),
+    ));
+
+    // Listener of the sound effects, which are placed around it to pan them
+    commands.spawn((SpatialListener::new(EAR_GAP), Transform::default()));
+
+    // Spawn the background sprite
+    commands.spawn((
+        Background,
+        Sprite::from_image(game_assets.background.clone()),
+    ));
+}
+
+// Reads a `--name <value>` command line argument, like `--seed 42` or
+// `--level tutorial`, for the game and the tools
+pub fn arg(name: &str) -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == name {
+            return args.next();
+        }
+    }
+    None
+}
+
+// Despawn all entities recursively with a given component
+pub fn cleanup<T: Component>(mut commands: Commands, query: Query<Entity, With<T>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+// Applies the sound settings to the effects being played. The music follows
+// them on its own.
+fn apply_volumes(
+    sound_enabled: Res<SoundEnabled>,
+    volumes: Res<Volumes>,
+    mut effects_query: Query<&mut SpatialAudioSink, With<SoundEffect>>,
+) {
+    for mut sink in &mut effects_query {
+        apply_volume(&mut *sink, volumes.effects_volume(), sound_enabled.0);
+    }
+}
+
+fn apply_volume(sink: &mut impl AudioSinkPlayback, volume: Volume, sound_enabled: bool) {
+    sink.set_volume(volume);
+    if sound_enabled {
+        sink.unmute();
+    } else {
+        sink.mute();
+    }
+}
+
+// Plays the sounds of the frame. When every voice is in use, a sound takes the
+// place of the oldest one of the lowest priority, unless that is higher than
+// its own.
+#[allow(clippy::too_many_arguments)]
+fn handle_sound_event(
+    mut commands: Commands,
+    audio_handles: Res<AudioHandles>,
+    mut sound_events: MessageReader<SoundEvent>,
+    sound_enabled: Res<SoundEnabled>,
+    volumes: Res<Volumes>,
+    time: Res<Time<Real>>,
+    mut cooldowns: ResMut<SoundCooldowns>,
+    voice_query: Query<(Entity, &SoundEffect)>,
+    camera_query: Query<&Transform, With<Camera>>,
+) {
+    if !sound_enabled.0 {
+        sound_events.clear();
+        return;
+    }
+
+    let now = time.elapsed_secs_f64();
+    let mut voices: Vec<(Entity, SoundEffect)> = voice_query
+        .iter()
+        .map(|(entity, voice)| (entity, *voice))
+        .collect();
+    let camera_x = camera_query
+        .single()
+        .map(|transform| transform.translation.x)
+        .unwrap_or_default();
+    for &SoundEvent { sound, position } in sound_events.read() {
+        if !cooldowns.try_play(sound, now) {
+            continue;
+        }
+
+        let priority = sound.priority();
+        if voices.len() >= MAX_VOICES {
+            let Some(index) = voices
+                .iter()
+                .enumerate()
+                .filter(|(_, (_, voice))| voice.priority <= priority)
+                .min_by(|(_, (_, a)), (_, (_, b))| {
+                    a.priority
+                        .cmp(&b.priority)
+                        .then(a.started.total_cmp(&b.started))
+                })
+                .map(|(index, _)| index)
+            else {
+                continue;
+            };
+            commands.entity(voices.swap_remove(index).0).despawn();
+        }
+
+        let source = match sound {
+            Sound::Click => audio_handles.click.clone(),
+            Sound::Coin => audio_handles.coin.clone(),
+            Sound::Jump => audio_handles.jump.clone(),
+            Sound::Highjump => audio_handles.highjump.clone(),
+            Sound::Hit => audio_handles.hit.clone(),
+        };
+        let voice = SoundEffect {
+            priority,
+            started: now,
+        };
+        let entity = commands
+            .spawn((
+                AudioPlayer::<AudioSource>(source),
+                PlaybackSettings::DESPAWN
+                    .with_volume(volumes.effects_volume())
+                    .with_spatial(true),
+                Transform::from_xyz(pan(position, camera_x) * EAR_GAP / 2.0, 0.0, 0.0),
+                voice,
+            ))
+            .id();
+        voices.push((entity, voice));
+    }
+}
+
+// From -1.0 (left ear) to 1.0 (right ear), by how far the sound is from the
+// middle of the screen. Sounds without a position play in the middle.
+fn pan(position: Option<Vec2>, camera_x: f32) -> f32 {
+    position.map_or(0.0, |position| {
+        ((position.x - camera_x) / (WORLD_WIDTH / 2.0)).clamp(-1.0, 1.0)
+    })
+}
+
+fn click_sound(mut sound_events: MessageWriter<SoundEvent>) {
+    sound_events.write(SoundEvent::new(Sound::Click));
+}
@@ -0,0 +1,150 @@
+use bevy::{asset::LoadState, prelude::*};
+
+use crate::{
+    GameState,
+    assets::{AudioHandles, GameAssets},
+    cleanup,
+};
+
+#[derive(Component)]
+struct LoadingEntity;
+
+// Filled part of the progress bar
+#[derive(Component)]
+struct ProgressFill;
+
+// Set once an asset has failed to load. The error screen stays up for good.
+#[derive(Resource)]
+struct LoadingFailed;
+
+const BAR_WIDTH: f32 = 200.0;
+const BAR_COLOR: Color = Color::srgb(0.3, 0.3, 0.3);
+
+pub struct LoadingPlugin;
+impl Plugin for LoadingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Loading), setup_loading)
+            .add_systems(OnExit(GameState::Loading), cleanup::<LoadingEntity>)
+            .add_systems(
+                Update,
+                update_loading.run_if(
+                    in_state(GameState::Loading).and(not(resource_exists::<LoadingFailed>)),
+                ),
+            );
+    }
+}
+
+// The font may not be loaded yet, so the loading screen is only a bar
+fn setup_loading(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            LoadingEntity,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    Node {
+                        width: Val::Px(BAR_WIDTH),
+                        height: Val::Px(8.0),
+                        ..default()
+                    },
+                    BackgroundColor(BAR_COLOR),
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        Node {
+                            width: Val::Percent(0.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        BackgroundColor(Color::WHITE),
+                        ProgressFill,
+                    ));
+                });
+        });
+}
+
+// Fills the progress bar with the share of the assets that have loaded, and
+// shows the menu once they all have. If one fails, the bar is replaced with
+// the errors.
+fn update_loading(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    audio_handles: Res<AudioHandles>,
+    asset_server: Res<AssetServer>,
+    mut fill_query: Query<&mut Node, With<ProgressFill>>,
+    loading_query: Query<Entity, With<LoadingEntity>>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    let mut loaded = 0;
+    let mut total = 0;
+    let mut errors = Vec::new();
+    for id in game_assets.ids().chain(audio_handles.ids()) {
+        total += 1;
+        if asset_server.is_loaded_with_dependencies(id) {
+            loaded += 1;
+        } else if let Some(LoadState::Failed(error)) = asset_server.get_load_state(id) {
+            errors.push(error.to_string());
+        }
+    }
+
+    if !errors.is_empty() {
+        commands.insert_resource(LoadingFailed);
+        for entity in &loading_query {
+            commands.entity(entity).despawn();
+        }
+        spawn_error_screen(&mut commands, &errors);
+        return;
+    }
+
+    for mut node in &mut fill_query {
+        node.width = Val::Percent(loaded as f32 * 100.0 / total as f32);
+    }
+    if loaded == total {
+        game_state.set(GameState::Menu);
+    }
+}
+
+// Uses the default font, as the game font may be the asset that failed
+fn spawn_error_screen(commands: &mut Commands, errors: &[String]) {
+    let text = |text: String, font_size: f32| {
+        (
+            Text::new(text),
+            TextFont {
+                font_size,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+            TextLayout::new_with_justify(Justify::Center),
+        )
+    };
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(10.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            BackgroundColor(Color::BLACK),
+            LoadingEntity,
+        ))
+        .with_children(|parent| {
+            parent.spawn(text("THE GAME COULD NOT BE LOADED".to_string(), 24.0));
+            for error in errors {
+                parent.spawn(text(error.clone(), 12.0));
+            }
+        });
+}
@@ -1,9 +1,13 @@
-#![allow(clippy::type_complexity)]
+#![allow(clippy::type_complexity, clippy::too_many_arguments)]
 
 use crate::{
-    GameMusic, GameState, SoundEnabled, cleanup, click_sound, settings::write_sound_setting,
+    GameState, SoundEnabled,
+    assets::GameAssets,
+    cleanup, click_sound,
+    game::{ActiveReplay, Replay},
+    settings::{ReplaySlot, write_sound_setting},
 };
-use bevy::{audio::Volume, prelude::*};
+use bevy::prelude::*;
 
 #[derive(Component)]
 struct MenuEntity;
@@ -16,6 +20,10 @@ enum MenuButtonAction {
     Play,
     HighScores,
     Help,
+    Editor,
+    Controls,
+    Options,
+    WatchReplay(ReplaySlot),
     SoundToggle,
 }
 
@@ -44,7 +52,7 @@ impl Plugin for MenuPlugin {
 
 fn setup_menu(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
+    game_assets: Res<GameAssets>,
     sound_enabled: ResMut<SoundEnabled>,
 ) {
     commands
@@ -60,7 +68,7 @@ fn setup_menu(
             MenuEntity,
         ))
         .with_children(|parent| {
-            let logo = asset_server.load("sprites/logo.png");
+            let logo = game_assets.logo.clone();
             parent.spawn((
                 ImageNode::new(logo),
                 Node {
@@ -71,18 +79,30 @@ fn setup_menu(
                 },
             ));
 
+            let replays = [
+                (ReplaySlot::Last, "LAST RUN"),
+                (ReplaySlot::Best, "BEST RUN"),
+            ]
+            .into_iter()
+            .filter(|(slot, _)| Replay::load(*slot).is_some())
+            .map(|(slot, text)| (MenuButtonAction::WatchReplay(slot), text));
+
             for (action, text) in [
                 (MenuButtonAction::Play, "PLAY"),
                 (MenuButtonAction::HighScores, "HIGHSCORES"),
                 (MenuButtonAction::Help, "HELP"),
-            ] {
+                (MenuButtonAction::Editor, "EDITOR"),
+            ]
+            .into_iter()
+            .chain(replays)
+            {
                 parent
                     .spawn((Button, BackgroundColor(TRANSPARENT), action))
                     .with_children(|parent| {
                         parent.spawn((
                             Text::new(text),
                             TextFont {
-                                font: asset_server.load("fonts/Retroville NC.ttf"),
+                                font: game_assets.font.clone(),
                                 font_size: 40.0,
                                 ..default()
                             },
@@ -107,14 +127,40 @@ fn setup_menu(
                     MenuButtonAction::SoundToggle,
                 ))
                 .with_children(|parent| {
-                    let path = if sound_enabled.0 {
-                        "sprites/soundOn.png"
-                    } else {
-                        "sprites/soundOff.png"
-                    };
-                    let icon = asset_server.load(path);
+                    let icon = game_assets.sound_icon(sound_enabled.0);
                     parent.spawn((ImageNode::new(icon), SoundButton));
                 });
+
+            parent
+                .spawn(Node {
+                    position_type: PositionType::Absolute,
+                    right: Val::Px(10.0),
+                    bottom: Val::Px(10.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::End,
+                    row_gap: Val::Px(10.0),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for (action, text) in [
+                        (MenuButtonAction::Options, "OPTIONS"),
+                        (MenuButtonAction::Controls, "CONTROLS"),
+                    ] {
+                        parent
+                            .spawn((Button, BackgroundColor(TRANSPARENT), action))
+                            .with_children(|parent| {
+                                parent.spawn((
+                                    Text::new(text),
+                                    TextFont {
+                                        font: game_assets.font.clone(),
+                                        font_size: 20.0,
+                                        ..default()
+                                    },
+                                    TextColor(Color::WHITE),
+                                ));
+                            });
+                    }
+                });
         });
 }
 
@@ -124,11 +170,10 @@ fn menu_action(
         (Changed<Interaction>, With<Button>),
     >,
     mut game_state: ResMut<NextState<GameState>>,
-    mut music_query: Query<&mut AudioSink, With<GameMusic>>,
     mut sound_enabled: ResMut<SoundEnabled>,
     mut sound_button_query: Query<(Entity, &mut ImageNode), With<SoundButton>>,
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
+    mut active_replay: ResMut<ActiveReplay>,
+    game_assets: Res<GameAssets>,
 ) {
     for (interaction, menu_button_action) in &interaction_query {
         if *interaction == Interaction::Pressed {
@@ -136,25 +181,20 @@ fn menu_action(
                 MenuButtonAction::Play => game_state.set(GameState::Playing),
                 MenuButtonAction::HighScores => game_state.set(GameState::HighScores),
                 MenuButtonAction::Help => game_state.set(GameState::Help),
-                MenuButtonAction::SoundToggle => {
-                    if let Ok(mut sink) = music_query.single_mut() {
-                        sink.toggle_mute();
-                    } else {
-                        commands.spawn((
-                            AudioPlayer::<AudioSource>(asset_server.load("audio/music.ogg")),
-                            PlaybackSettings::LOOP.with_volume(Volume::Linear(0.1)),
-                            GameMusic,
-                        ));
+                MenuButtonAction::Editor => game_state.set(GameState::Editor),
+                MenuButtonAction::Controls => game_state.set(GameState::Controls),
+                MenuButtonAction::Options => game_state.set(GameState::Options),
+                MenuButtonAction::WatchReplay(slot) => {
+                    if let Some(replay) = Replay::load(*slot) {
+                        active_replay.start(replay);
+                        game_state.set(GameState::Playing);
                     }
+                }
+                MenuButtonAction::SoundToggle => {
                     sound_enabled.0 = !sound_enabled.0;
 
                     let (_, mut ui_image) = sound_button_query.single_mut().unwrap();
-                    let path = if sound_enabled.0 {
-                        "sprites/soundOn.png"
-                    } else {
-                        "sprites/soundOff.png"
-                    };
-                    *ui_image = ImageNode::new(asset_server.load(path));
+                    *ui_image = ImageNode::new(game_assets.sound_icon(sound_enabled.0));
 
                     write_sound_setting(sound_enabled.0);
                 }
@@ -0,0 +1,224 @@
+#![allow(clippy::too_many_arguments)]
+
+use bevy::{audio::Volume, prelude::*};
+
+use crate::{
+    GameState, SoundEnabled, Volumes, apply_volume,
+    assets::AudioHandles,
+    game::{
+        GameObjects, PlayState,
+        bob::Bob,
+        level::GameObjectType,
+        physics::{CameraHeight, Position},
+        squirrel::Squirrel,
+    },
+};
+
+// How fast tracks fade in and out, and the music ducks, in volume per second
+const FADE_SPEED: f32 = 1.5;
+// Volume of the game music while a squirrel is on screen
+const DUCKED_VOLUME: f32 = 0.4;
+// Volume of the game music while the game over sting plays
+const STING_DUCKED_VOLUME: f32 = 0.1;
+const STING_SECONDS: f32 = 1.5;
+const STING_SPEED: f32 = 0.5;
+// Volume and tempo of the game music, from the bottom of the level to the
+// castle
+const CALM_VOLUME: f32 = 0.7;
+const INTENSE_SPEED: f32 = 1.12;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MusicTrack {
+    Game,
+    Calm,
+}
+
+impl MusicTrack {
+    const ALL: [MusicTrack; 2] = [MusicTrack::Game, MusicTrack::Calm];
+
+    // The calm track is a slower, lower rendition of the game music
+    fn speed(self) -> f32 {
+        match self {
+            MusicTrack::Game => 1.0,
+            MusicTrack::Calm => 0.8,
+        }
+    }
+
+    // The screens to rest on get the calm track
+    pub fn for_state(state: GameState) -> Self {
+        match state {
+            GameState::WinScreen | GameState::Help => MusicTrack::Calm,
+            _ => MusicTrack::Game,
+        }
+    }
+}
+
+// One looping track of the music. Tracks crossfade into each other.
+#[derive(Component)]
+pub struct GameMusic {
+    track: MusicTrack,
+    // From 0.0 (faded out) to 1.0
+    fade: f32,
+}
+
+// How the game music follows the run
+#[derive(Resource, Default)]
+struct MusicMix {
+    // From 0.0 at the bottom of the level to 1.0 at the castle
+    intensity: f32,
+    // Volume of the game music, lowered while there is danger
+    duck: f32,
+    // Seconds left of the game over sting
+    sting: f32,
+}
+
+pub struct MusicPlugin;
+impl Plugin for MusicPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(MusicMix {
+            duck: 1.0,
+            ..default()
+        })
+        .add_systems(Update, (spawn_music, update_music).chain())
+        .add_systems(OnEnter(PlayState::GameOver), play_sting);
+    }
+}
+
+fn spawn_music(
+    mut commands: Commands,
+    audio_handles: Res<AudioHandles>,
+    music_query: Query<(), With<GameMusic>>,
+    sound_enabled: Res<SoundEnabled>,
+) {
+    // The music is only loaded once sound is enabled
+    if !sound_enabled.0 || !music_query.is_empty() {
+        return;
+    }
+
+    for track in MusicTrack::ALL {
+        commands.spawn((
+            AudioPlayer::<AudioSource>(audio_handles.music.clone()),
+            PlaybackSettings::LOOP
+                .with_volume(Volume::SILENT)
+                .with_speed(track.speed()),
+            GameMusic { track, fade: 0.0 },
+        ));
+    }
+}
+
+// Fades to the track of the screen. In a run, the game music speeds up and
+// gets louder as Bob climbs, and ducks while a squirrel is on screen or the
+// sting plays.
+fn update_music(
+    mut music_query: Query<(&mut GameMusic, Option<&mut AudioSink>)>,
+    mut mix: ResMut<MusicMix>,
+    game_state: Res<State<GameState>>,
+    play_state: Res<State<PlayState>>,
+    bob_query: Query<&Position, With<Bob>>,
+    squirrel_query: Query<&Position, With<Squirrel>>,
+    game_objects: Res<GameObjects>,
+    camera_height: Res<CameraHeight>,
+    sound_enabled: Res<SoundEnabled>,
+    volumes: Res<Volumes>,
+    time: Res<Time<Real>>,
+) {
+    let playing = *game_state == GameState::Playing;
+    let fade_step = FADE_SPEED * time.delta_secs();
+    mix.sting = (mix.sting - time.delta_secs()).max(0.0);
+
+    mix.intensity = match bob_query.single() {
+        Ok(bob_position) if playing => climb_progress(bob_position, &game_objects),
+        _ => 0.0,
+    };
+    let danger = playing
+        && *play_state == PlayState::Running
+        && squirrel_query
+            .iter()
+            .any(|position| (position.y - camera_height.0).abs() < 240.0);
+    let duck = if mix.sting > 0.0 {
+        STING_DUCKED_VOLUME
+    } else if danger {
+        DUCKED_VOLUME
+    } else {
+        1.0
+    };
+    mix.duck = move_towards(mix.duck, duck, fade_step);
+
+    let current_track = MusicTrack::for_state(*game_state.get());
+    for (mut music, sink) in &mut music_query {
+        let fade = if music.track == current_track {
+            1.0
+        } else {
+            0.0
+        };
+        music.fade = move_towards(music.fade, fade, fade_step);
+
+        let Some(mut sink) = sink else {
+            continue;
+        };
+        // A track that is faded out is paused, so it isn't decoded for nothing
+        if music.fade > 0.0 {
+            sink.play();
+        } else {
+            sink.pause();
+            continue;
+        }
+        let (gain, speed) = match music.track {
+            MusicTrack::Game => (
+                mix.duck * CALM_VOLUME.lerp(1.0, mix.intensity),
+                1.0_f32.lerp(INTENSE_SPEED, mix.intensity),
+            ),
+            MusicTrack::Calm => (1.0, 1.0),
+        };
+        apply_volume(
+            &mut *sink,
+            volumes.music_volume() * Volume::Linear(music.fade * gain),
+            sound_enabled.0,
+        );
+        sink.set_speed(music.track.speed() * speed);
+    }
+}
+
+// How far Bob has climbed towards the castle, from 0.0 to 1.0
+fn climb_progress(bob_position: &Position, game_objects: &GameObjects) -> f32 {
+    let castle_height = game_objects
+        .0
+        .iter()
+        .find(|object| object.object_type == GameObjectType::Castle)
+        .map_or(0.0, |castle| castle.y);
+    if castle_height <= 0.0 {
+        return 0.0;
+    }
+    // Positions are relative to the middle of the first screen
+    ((bob_position.y + 240.0) / castle_height).clamp(0.0, 1.0)
+}
+
+fn move_towards(value: f32, target: f32, step: f32) -> f32 {
+    if value < target {
+        (value + step).min(target)
+    } else {
+        (value - step).max(target)
+    }
+}
+
+// A low version of the hit sound marks the end of the run, over the ducked
+// music
+fn play_sting(
+    mut commands: Commands,
+    audio_handles: Res<AudioHandles>,
+    mut mix: ResMut<MusicMix>,
+    sound_enabled: Res<SoundEnabled>,
+    volumes: Res<Volumes>,
+) {
+    if !sound_enabled.0 {
+        return;
+    }
+
+    mix.sting = STING_SECONDS;
+    commands.spawn((
+        AudioPlayer::<AudioSource>(audio_handles.hit.clone()),
+        PlaybackSettings::DESPAWN
+            .with_volume(volumes.music_volume())
+            .with_speed(STING_SPEED),
+    ));
+}
@@ -0,0 +1,260 @@
+#![allow(clippy::type_complexity, clippy::too_many_arguments)]
+
+use crate::{
+    GameState,
+    assets::GameAssets,
+    cleanup, click_sound,
+    focus::{BackButton, Focus},
+    highscores::{DEFAULT_NAME, HighScores, PendingHighScore, PlayerName, save_pending_highscore},
+    input::{Action, Actions},
+    leaderboard::{Leaderboard, MAX_NAME_LENGTH},
+};
+use bevy::{
+    input::keyboard::{Key, KeyboardInput},
+    prelude::*,
+};
+
+const LETTERS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+#[derive(Component)]
+struct NameEntryEntity;
+
+#[derive(Component)]
+struct NameText;
+
+#[derive(Component)]
+enum NameButtonAction {
+    Letter(char),
+    Delete,
+    Done,
+}
+
+// Name being typed
+#[derive(Resource, Default)]
+struct EnteredName(String);
+
+const TRANSPARENT: Color = Color::linear_rgba(0.0, 0.0, 0.0, 0.0);
+
+pub struct NameEntryPlugin;
+impl Plugin for NameEntryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EnteredName>()
+            .add_systems(OnEnter(GameState::NameEntry), setup_name_entry)
+            .add_systems(
+                OnExit(GameState::NameEntry),
+                (click_sound, cleanup::<NameEntryEntity>),
+            )
+            .add_systems(
+                Update,
+                (
+                    (ui_action, keyboard_input),
+                    update_name_text.run_if(resource_changed::<EnteredName>),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::NameEntry)),
+            );
+    }
+}
+
+fn setup_name_entry(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    pending: Res<PendingHighScore>,
+    mut entered_name: ResMut<EnteredName>,
+) {
+    entered_name.0.clear();
+    let score = pending.0.as_ref().map_or(0, |entry| entry.score);
+    let font = game_assets.font.clone();
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(10.0),
+                ..default()
+            },
+            NameEntryEntity,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(format!("NEW HIGHSCORE: {}", score)),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 30.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                TextLayout::new_with_justify(Justify::Center),
+            ));
+            parent.spawn((
+                Text::new("_"),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 40.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                TextLayout::new_with_justify(Justify::Center),
+                NameText,
+            ));
+
+            // On-screen letters, for touch screens
+            parent
+                .spawn(Node {
+                    width: Val::Px(320.0),
+                    flex_wrap: FlexWrap::Wrap,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                })
+                .with_children(|parent| {
+                    let buttons = LETTERS
+                        .chars()
+                        .map(|letter| (NameButtonAction::Letter(letter), letter.to_string()))
+                        .chain([
+                            (NameButtonAction::Delete, "DEL".to_string()),
+                            (NameButtonAction::Done, "OK".to_string()),
+                        ]);
+                    for (action, text) in buttons {
+                        let is_back = matches!(action, NameButtonAction::Delete);
+                        parent
+                            .spawn((
+                                Button,
+                                Node {
+                                    min_width: Val::Px(40.0),
+                                    height: Val::Px(40.0),
+                                    align_items: AlignItems::Center,
+                                    justify_content: JustifyContent::Center,
+                                    ..default()
+                                },
+                                BackgroundColor(TRANSPARENT),
+                                action,
+                            ))
+                            .insert_if(BackButton, || is_back)
+                            .with_children(|parent| {
+                                parent.spawn((
+                                    Text::new(text),
+                                    TextFont {
+                                        font: font.clone(),
+                                        font_size: 24.0,
+                                        ..default()
+                                    },
+                                    TextColor(Color::WHITE),
+                                    TextLayout::new_with_justify(Justify::Center),
+                                ));
+                            });
+                    }
+                });
+        });
+}
+
+fn ui_action(
+    interaction_query: Query<
+        (&Interaction, &NameButtonAction),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut entered_name: ResMut<EnteredName>,
+    mut high_scores: ResMut<HighScores>,
+    mut pending: ResMut<PendingHighScore>,
+    mut player_name: ResMut<PlayerName>,
+    leaderboard: Res<Leaderboard>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    for (interaction, action) in &interaction_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match action {
+            NameButtonAction::Letter(letter) => type_letter(&mut entered_name, *letter),
+            NameButtonAction::Delete => {
+                entered_name.0.pop();
+            }
+            NameButtonAction::Done => finish(
+                &entered_name,
+                &mut high_scores,
+                &mut pending,
+                &mut player_name,
+                &leaderboard,
+                &mut game_state,
+            ),
+        }
+    }
+}
+
+fn keyboard_input(
+    mut keyboard_events: MessageReader<KeyboardInput>,
+    actions: Actions,
+    focus: Res<Focus>,
+    mut entered_name: ResMut<EnteredName>,
+    mut high_scores: ResMut<HighScores>,
+    mut pending: ResMut<PendingHighScore>,
+    mut player_name: ResMut<PlayerName>,
+    leaderboard: Res<Leaderboard>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    // Confirm presses the focused button instead, if there is one
+    if actions.just_pressed_without_pointer(Action::Confirm) && focus.get().is_none() {
+        keyboard_events.clear();
+        finish(
+            &entered_name,
+            &mut high_scores,
+            &mut pending,
+            &mut player_name,
+            &leaderboard,
+            &mut game_state,
+        );
+        return;
+    }
+
+    for event in keyboard_events.read() {
+        if !event.state.is_pressed() {
+            continue;
+        }
+
+        if event.logical_key == Key::Backspace {
+            entered_name.0.pop();
+        } else {
+            for letter in event.text.iter().flat_map(|text| text.chars()) {
+                type_letter(&mut entered_name, letter);
+            }
+        }
+    }
+}
+
+// Names are upper case, which is all the game's font has
+fn type_letter(entered_name: &mut EnteredName, letter: char) {
+    if entered_name.0.len() < MAX_NAME_LENGTH && letter.is_ascii_alphanumeric() {
+        entered_name.0.push(letter.to_ascii_uppercase());
+    }
+}
+
+fn finish(
+    entered_name: &EnteredName,
+    high_scores: &mut HighScores,
+    pending: &mut PendingHighScore,
+    player_name: &mut PlayerName,
+    leaderboard: &Leaderboard,
+    game_state: &mut NextState<GameState>,
+) {
+    let name = if entered_name.0.is_empty() {
+        DEFAULT_NAME.to_string()
+    } else {
+        entered_name.0.clone()
+    };
+    save_pending_highscore(high_scores, pending, player_name, leaderboard, name);
+    game_state.set(GameState::HighScores);
+}
+
+fn update_name_text(
+    query: Query<Entity, With<NameText>>,
+    mut writer: TextUiWriter,
+    entered_name: Res<EnteredName>,
+) {
+    if let Ok(entity) = query.single() {
+        *writer.text(entity, 0) = format!("{}_", entered_name.0);
+    }
+}
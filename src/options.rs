@@ -0,0 +1,242 @@
+#![allow(clippy::type_complexity)]
+
+use bevy::prelude::*;
+
+use crate::{
+    GameState, SoundEnabled, VolumeChannel, Volumes,
+    assets::GameAssets,
+    cleanup, click_sound,
+    focus::BackButton,
+    settings::{write_sound_setting, write_volumes},
+};
+
+#[derive(Component)]
+struct OptionsEntity;
+
+// Filled part of the bar of a volume slider
+#[derive(Component)]
+struct VolumeFill(VolumeChannel);
+
+#[derive(Component)]
+struct VolumeText(VolumeChannel);
+
+#[derive(Component)]
+struct SoundText;
+
+#[derive(Component)]
+enum OptionsButtonAction {
+    // Turns the volume down or up one step
+    Adjust(VolumeChannel, i32),
+    SoundToggle,
+    Back,
+}
+
+const TRANSPARENT: Color = Color::linear_rgba(0.0, 0.0, 0.0, 0.0);
+const BAR_WIDTH: f32 = 120.0;
+const BAR_COLOR: Color = Color::srgb(0.3, 0.3, 0.3);
+
+pub struct OptionsPlugin;
+impl Plugin for OptionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Options), setup_options)
+            .add_systems(
+                OnExit(GameState::Options),
+                (click_sound, cleanup::<OptionsEntity>),
+            )
+            .add_systems(
+                Update,
+                (
+                    ui_action,
+                    update_volume_sliders.run_if(resource_changed::<Volumes>),
+                    update_sound_text.run_if(resource_changed::<SoundEnabled>),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Options)),
+            );
+    }
+}
+
+fn setup_options(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    volumes: Res<Volumes>,
+    sound_enabled: Res<SoundEnabled>,
+) {
+    let font = game_assets.font.clone();
+    let text = |text: String, font_size: f32| {
+        (
+            Text::new(text),
+            TextFont {
+                font: font.clone(),
+                font_size,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+            TextLayout::new_with_justify(Justify::Center),
+        )
+    };
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(10.0),
+                ..default()
+            },
+            OptionsEntity,
+        ))
+        .with_children(|parent| {
+            parent.spawn(text("OPTIONS".to_string(), 40.0));
+
+            for channel in VolumeChannel::ALL {
+                let volume = volumes.get(channel);
+                parent.spawn(text(channel.name().to_string(), 24.0));
+                parent
+                    .spawn(Node {
+                        align_items: AlignItems::Center,
+                        column_gap: Val::Px(10.0),
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent
+                            .spawn((
+                                Button,
+                                BackgroundColor(TRANSPARENT),
+                                OptionsButtonAction::Adjust(channel, -1),
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn(text("-".to_string(), 24.0));
+                            });
+
+                        parent
+                            .spawn((
+                                Node {
+                                    width: Val::Px(BAR_WIDTH),
+                                    height: Val::Px(8.0),
+                                    ..default()
+                                },
+                                BackgroundColor(BAR_COLOR),
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn((
+                                    Node {
+                                        width: Val::Percent(volume * 100.0),
+                                        height: Val::Percent(100.0),
+                                        ..default()
+                                    },
+                                    BackgroundColor(Color::WHITE),
+                                    VolumeFill(channel),
+                                ));
+                            });
+
+                        parent
+                            .spawn((
+                                Button,
+                                BackgroundColor(TRANSPARENT),
+                                OptionsButtonAction::Adjust(channel, 1),
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn(text("+".to_string(), 24.0));
+                            });
+
+                        parent.spawn((text(volume_text(volume), 16.0), VolumeText(channel)));
+                    });
+            }
+
+            parent
+                .spawn((
+                    Button,
+                    BackgroundColor(TRANSPARENT),
+                    OptionsButtonAction::SoundToggle,
+                ))
+                .with_children(|parent| {
+                    parent.spawn((text(sound_text(sound_enabled.0), 24.0), SoundText));
+                });
+
+            parent
+                .spawn((
+                    Button,
+                    Node {
+                        position_type: PositionType::Absolute,
+                        left: Val::Px(10.0),
+                        bottom: Val::Px(10.0),
+                        width: Val::Px(64.0),
+                        height: Val::Px(64.0),
+                        ..default()
+                    },
+                    BackgroundColor(TRANSPARENT),
+                    OptionsButtonAction::Back,
+                    BackButton,
+                ))
+                .with_children(|parent| {
+                    let icon = game_assets.back.clone();
+                    parent.spawn(ImageNode::new(icon));
+                });
+        });
+}
+
+fn volume_text(volume: f32) -> String {
+    format!("{:>3}%", (volume * 100.0).round())
+}
+
+fn sound_text(sound_enabled: bool) -> String {
+    if sound_enabled {
+        "SOUND: ON".to_string()
+    } else {
+        "SOUND: OFF".to_string()
+    }
+}
+
+fn ui_action(
+    interaction_query: Query<
+        (&Interaction, &OptionsButtonAction),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut game_state: ResMut<NextState<GameState>>,
+    mut volumes: ResMut<Volumes>,
+    mut sound_enabled: ResMut<SoundEnabled>,
+) {
+    for (interaction, action) in &interaction_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match action {
+            OptionsButtonAction::Adjust(channel, steps) => {
+                volumes.adjust(*channel, *steps);
+                write_volumes(&volumes);
+            }
+            OptionsButtonAction::SoundToggle => {
+                sound_enabled.0 = !sound_enabled.0;
+                write_sound_setting(sound_enabled.0);
+            }
+            OptionsButtonAction::Back => game_state.set(GameState::Menu),
+        }
+    }
+}
+
+fn update_volume_sliders(
+    mut fill_query: Query<(&VolumeFill, &mut Node)>,
+    mut text_query: Query<(&VolumeText, &mut Text)>,
+    volumes: Res<Volumes>,
+) {
+    for (VolumeFill(channel), mut node) in &mut fill_query {
+        node.width = Val::Percent(volumes.get(*channel) * 100.0);
+    }
+    for (VolumeText(channel), mut text) in &mut text_query {
+        text.0 = volume_text(volumes.get(*channel));
+    }
+}
+
+fn update_sound_text(
+    mut query: Query<&mut Text, With<SoundText>>,
+    sound_enabled: Res<SoundEnabled>,
+) {
+    for mut text in &mut query {
+        text.0 = sound_text(sound_enabled.0);
+    }
+}
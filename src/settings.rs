@@ -1,86 +1,293 @@
-use std::{error::Error, fs};
+use std::{
+    error::Error,
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use bevy::log::warn;
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Volumes,
+    highscores::HighScoreEntry,
+    input::{Bindings, TouchSteering},
+};
 
 pub const HIGHSCORE_COUNT: usize = 5;
 
-const SETTINGS_FILE: &str = ".superjumper";
+// Version of the settings file format. Settings of older versions are
+// migrated when read.
+const SETTINGS_VERSION: u32 = 2;
+const APP_DIR: &str = "superjumper";
+const SETTINGS_FILE: &str = "settings.ron";
+// Line-based settings of earlier versions of the game, in the working directory
+const LEGACY_SETTINGS_FILE: &str = ".superjumper";
+// Where the legacy settings are moved once migrated, so they aren't read again
+const MIGRATED_LEGACY_SETTINGS_FILE: &str = ".superjumper.migrated";
+const LAST_REPLAY_FILE: &str = "last.replay";
+const BEST_REPLAY_FILE: &str = "best.replay";
+const GHOSTS_DIR: &str = "ghosts";
+
+#[derive(Clone, Copy)]
+pub enum ReplaySlot {
+    Last,
+    Best,
+}
+
+impl ReplaySlot {
+    fn file_name(self) -> &'static str {
+        match self {
+            ReplaySlot::Last => LAST_REPLAY_FILE,
+            ReplaySlot::Best => BEST_REPLAY_FILE,
+        }
+    }
+}
 
+// Missing fields keep their default value, so settings files of older
+// versions can still be read
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
 pub struct Settings {
+    version: u32,
     pub sound_enabled: bool,
-    pub high_scores: [u32; HIGHSCORE_COUNT],
+    pub volumes: Volumes,
+    pub high_scores: Vec<HighScoreEntry>,
+    // Address of a leaderboard server, like "http://localhost:8080"
+    pub leaderboard_url: Option<String>,
+    // Name the player entered last, for runs that don't make the high scores
+    pub player_name: Option<String>,
+    pub bindings: Bindings,
+    pub touch_steering: TouchSteering,
 }
 
 impl Default for Settings {
     fn default() -> Self {
         const DEFAULT_HIGHSCORES: [u32; HIGHSCORE_COUNT] = [100, 80, 50, 30, 10];
 
-        let mut high_scores: [u32; HIGHSCORE_COUNT] = [0; HIGHSCORE_COUNT];
-        for (i, score) in DEFAULT_HIGHSCORES.iter().enumerate() {
-            high_scores[i] = *score;
-        }
-
         Settings {
+            version: SETTINGS_VERSION,
             sound_enabled: true,
-            high_scores,
+            volumes: Volumes::default(),
+            high_scores: legacy_high_scores(DEFAULT_HIGHSCORES, "BOB"),
+            leaderboard_url: None,
+            player_name: None,
+            bindings: Bindings::default(),
+            touch_steering: TouchSteering::default(),
         }
     }
 }
 
-pub fn read_settings() -> Settings {
-    if let Ok(settings) = read_settings_file() {
-        return settings;
-    }
-    Settings::default()
+#[derive(Deserialize)]
+struct SettingsVersion {
+    #[serde(default)]
+    version: u32,
+}
+
+// Version 1 only stored the high scores, without names or run statistics
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct SettingsV1 {
+    sound_enabled: bool,
+    high_scores: [u32; HIGHSCORE_COUNT],
 }
 
-fn read_settings_file() -> Result<Settings, Box<dyn Error>> {
-    let contents = fs::read_to_string(SETTINGS_FILE)?;
-    let mut sound_enabled = false;
-    let mut high_scores: [u32; HIGHSCORE_COUNT] = [0; HIGHSCORE_COUNT];
-    let mut high_score_index: usize = 0;
+fn legacy_high_scores(scores: [u32; HIGHSCORE_COUNT], name: &str) -> Vec<HighScoreEntry> {
+    scores
+        .into_iter()
+        .map(|score| HighScoreEntry::unnamed(name, score))
+        .collect()
+}
 
-    for (i, line) in contents.lines().enumerate() {
-        if i == 0 {
-            match line {
-                "true" => sound_enabled = true,
-                "false" => sound_enabled = false,
-                _ => Err("Invalid file")?,
+impl Settings {
+    pub fn from_ron(contents: &str) -> Result<Self, Box<dyn Error>> {
+        let version: SettingsVersion = ron::from_str(contents)?;
+        let mut settings = match version.version {
+            1 => {
+                let settings: SettingsV1 = ron::from_str(contents)?;
+                Settings {
+                    sound_enabled: settings.sound_enabled,
+                    high_scores: legacy_high_scores(settings.high_scores, "PLAYER"),
+                    ..Settings::default()
+                }
             }
-        } else if i > 0 && i <= HIGHSCORE_COUNT {
-            if let Ok(score) = line.parse::<u32>() {
-                high_scores[i - 1] = score;
-                high_score_index = i;
+            // Saving them would drop whatever the newer version added
+            version if version > SETTINGS_VERSION => Err(format!(
+                "Settings of version {} are newer than this version of the game",
+                version
+            ))?,
+            _ => ron::from_str(contents)?,
+        };
+        settings.version = SETTINGS_VERSION;
+        Ok(settings)
+    }
+
+    pub fn to_ron(&self) -> Result<String, Box<dyn Error>> {
+        Ok(ron::ser::to_string_pretty(self, PrettyConfig::default())?)
+    }
+
+    // Reads the sound setting line followed by one line per high score
+    pub fn from_legacy(contents: &str) -> Result<Self, Box<dyn Error>> {
+        let mut sound_enabled = false;
+        let mut high_scores: [u32; HIGHSCORE_COUNT] = [0; HIGHSCORE_COUNT];
+        let mut high_score_index: usize = 0;
+
+        for (i, line) in contents.lines().enumerate() {
+            if i == 0 {
+                match line {
+                    "true" => sound_enabled = true,
+                    "false" => sound_enabled = false,
+                    _ => Err("Invalid file")?,
+                }
+            } else if i > 0 && i <= HIGHSCORE_COUNT {
+                if let Ok(score) = line.parse::<u32>() {
+                    high_scores[i - 1] = score;
+                    high_score_index = i;
+                }
+            } else if !line.is_empty() {
+                Err("Invalid file")?
             }
-        } else if !line.is_empty() {
+        }
+
+        if high_score_index != HIGHSCORE_COUNT {
             Err("Invalid file")?
         }
-    }
 
-    if high_score_index != HIGHSCORE_COUNT {
-        Err("Invalid file")?
+        Ok(Settings {
+            sound_enabled,
+            high_scores: legacy_high_scores(high_scores, "PLAYER"),
+            ..Settings::default()
+        })
     }
+}
 
-    Ok(Settings {
-        sound_enabled,
-        high_scores,
-    })
+// The settings, replays and ghosts live in the platform's config directory,
+// or in the working directory where there is none
+fn app_dir() -> PathBuf {
+    dirs::config_dir()
+        .map(|dir| dir.join(APP_DIR))
+        .unwrap_or_default()
 }
 
-pub fn write_sound_setting(sound_enabled: bool) {
-    let mut data = format!("{}\n", sound_enabled);
+fn settings_path() -> PathBuf {
+    app_dir().join(SETTINGS_FILE)
+}
 
-    for score in read_settings().high_scores {
-        data.push_str(&format!("{}\n", score));
+fn replay_path(slot: ReplaySlot) -> PathBuf {
+    app_dir().join(slot.file_name())
+}
+
+// A settings file that can't be read gives the default settings, but is left
+// for the player to fix rather than overwritten
+pub fn read_settings() -> Settings {
+    let path = settings_path();
+    match fs::read_to_string(&path) {
+        Ok(contents) => Settings::from_ron(&contents).unwrap_or_else(|err| {
+            warn!("Couldn't read the settings in {}: {}", path.display(), err);
+            Settings::default()
+        }),
+        Err(err) if err.kind() == ErrorKind::NotFound => read_legacy_settings(),
+        Err(err) => {
+            warn!("Couldn't read the settings in {}: {}", path.display(), err);
+            Settings::default()
+        }
+    }
+}
+
+// Migrates the settings of earlier versions, if there are any, and moves
+// their file out of the way once the new one is written
+fn read_legacy_settings() -> Settings {
+    let Ok(contents) = fs::read_to_string(LEGACY_SETTINGS_FILE) else {
+        return Settings::default();
+    };
+    let Ok(settings) = Settings::from_legacy(&contents) else {
+        return Settings::default();
+    };
+
+    if write_settings_file(&settings_path(), &settings).is_ok() {
+        let _ = fs::rename(LEGACY_SETTINGS_FILE, MIGRATED_LEGACY_SETTINGS_FILE);
     }
+    settings
+}
 
-    let _ = fs::write(SETTINGS_FILE, data);
+// Changes the settings in the file at the given path, which is created if
+// missing. A file that can't be read is an error and is left as it is, as
+// writing it back would lose every setting in it.
+pub fn update_settings_file(
+    path: &Path,
+    change: impl FnOnce(&mut Settings),
+) -> Result<(), Box<dyn Error>> {
+    let mut settings = match fs::read_to_string(path) {
+        Ok(contents) => Settings::from_ron(&contents)?,
+        Err(err) if err.kind() == ErrorKind::NotFound => Settings::default(),
+        Err(err) => Err(err)?,
+    };
+    change(&mut settings);
+    write_settings_file(path, &settings)
 }
 
-pub fn write_high_scores(high_scores: [u32; HIGHSCORE_COUNT]) {
-    let mut data = format!("{}\n", read_settings().sound_enabled);
+fn update_settings(change: impl FnOnce(&mut Settings)) {
+    let path = settings_path();
+    if let Err(err) = update_settings_file(&path, change) {
+        warn!("Couldn't save the settings to {}: {}", path.display(), err);
+    }
+}
 
-    for score in high_scores {
-        data.push_str(&format!("{}\n", score));
+// Writes a temporary file and renames it, so the settings are never left
+// half written
+fn write_settings_file(path: &Path, settings: &Settings) -> Result<(), Box<dyn Error>> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
     }
+    let temp_path = path.with_extension("ron.tmp");
+    fs::write(&temp_path, settings.to_ron()?)?;
+    fs::rename(&temp_path, path)?;
+    Ok(())
+}
+
+pub fn write_sound_setting(sound_enabled: bool) {
+    update_settings(|settings| settings.sound_enabled = sound_enabled);
+}
+
+pub fn write_volumes(volumes: &Volumes) {
+    update_settings(|settings| settings.volumes = *volumes);
+}
+
+pub fn write_high_scores(high_scores: &[HighScoreEntry]) {
+    update_settings(|settings| settings.high_scores = high_scores.to_vec());
+}
+
+pub fn write_player_name(name: &str) {
+    update_settings(|settings| settings.player_name = Some(name.to_string()));
+}
+
+pub fn write_bindings(bindings: &Bindings) {
+    update_settings(|settings| settings.bindings = bindings.clone());
+}
+
+pub fn write_touch_steering(touch_steering: TouchSteering) {
+    update_settings(|settings| settings.touch_steering = touch_steering);
+}
+
+pub fn read_replay(slot: ReplaySlot) -> Option<Vec<u8>> {
+    fs::read(replay_path(slot)).ok()
+}
+
+pub fn write_replay(slot: ReplaySlot, data: &[u8]) {
+    let _ = fs::create_dir_all(app_dir());
+    let _ = fs::write(replay_path(slot), data);
+}
+
+fn ghost_path(name: &str) -> PathBuf {
+    app_dir().join(GHOSTS_DIR).join(format!("{}.ghost", name))
+}
+
+pub fn read_ghost(name: &str) -> Option<Vec<u8>> {
+    fs::read(ghost_path(name)).ok()
+}
 
-    let _ = fs::write(SETTINGS_FILE, data);
+pub fn write_ghost(name: &str, data: &[u8]) {
+    let _ = fs::create_dir_all(app_dir().join(GHOSTS_DIR));
+    let _ = fs::write(ghost_path(name), data);
 }
@@ -1,4 +1,10 @@
-use crate::{GameState, cleanup, click_sound};
+use crate::{
+    GameState,
+    assets::GameAssets,
+    cleanup, click_sound,
+    highscores::{PendingHighScore, after_run_state},
+    input::confirm_just_pressed,
+};
 use bevy::prelude::*;
 
 #[derive(Component)]
@@ -32,7 +38,8 @@ impl Plugin for WinScreenPlugin {
             .add_systems(
                 Update,
                 (
-                    show_next_screen.run_if(in_state(GameState::WinScreen).and(has_user_input)),
+                    show_next_screen
+                        .run_if(in_state(GameState::WinScreen).and(confirm_just_pressed)),
                     click_sound.run_if(
                         resource_changed::<WinScreenIndex>
                             .and(not(resource_added::<WinScreenIndex>)),
@@ -42,43 +49,23 @@ impl Plugin for WinScreenPlugin {
     }
 }
 
-fn setup_winscreen(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    mut texture_atlases: ResMut<Assets<TextureAtlasLayout>>,
-) {
+fn setup_winscreen(mut commands: Commands, game_assets: Res<GameAssets>) {
     commands.spawn((
         WinScreenEntity,
-        Sprite::from_image(asset_server.load("sprites/castle.png")),
+        Sprite::from_image(game_assets.castle.clone()),
         Transform::from_xyz(0.0, 0.0, 100.0).with_scale(Vec3::new(3.0, 3.0, 1.0)),
     ));
 
-    // Load the bob's sprite sheet and create a texture atlas from it
-    let bob_texture = asset_server.load("sprites/bob.png");
-    let layout_handle = texture_atlases.add(TextureAtlasLayout::from_grid(
-        UVec2::new(32, 32),
-        5,
-        1,
-        None,
-        None,
-    ));
-
     // Spawn bob
     commands.spawn((
         WinScreenEntity,
-        Sprite::from_atlas_image(
-            bob_texture,
-            TextureAtlas {
-                layout: layout_handle,
-                index: 0,
-            },
-        ),
+        game_assets.bob_sprite(),
         Transform::from_xyz(-20.0, -5.0, 110.0),
     ));
 
     commands.spawn((
         WinScreenEntity,
-        Sprite::from_image(asset_server.load("sprites/princess.png")),
+        Sprite::from_image(game_assets.princess.clone()),
         Transform::from_xyz(20.0, -5.0, 110.0)
             .with_rotation(Quat::from_rotation_y(std::f32::consts::PI)),
     ));
@@ -98,7 +85,7 @@ fn setup_winscreen(
             builder.spawn((
                 Text::new(MESSAGES[0]),
                 TextFont {
-                    font: asset_server.load("fonts/Retroville NC.ttf"),
+                    font: game_assets.font.clone(),
                     font_size: 26.0,
                     ..default()
                 },
@@ -115,6 +102,7 @@ fn show_next_screen(
     mut writer: TextUiWriter,
     mut win_screen: ResMut<WinScreenIndex>,
     mut state: ResMut<NextState<GameState>>,
+    pending: Res<PendingHighScore>,
 ) {
     win_screen.0 += 1;
     if win_screen.0 < 7 {
@@ -122,16 +110,6 @@ fn show_next_screen(
         *writer.text(entity, 0) = MESSAGES[win_screen.0].to_string();
     } else {
         win_screen.0 = 0;
-        state.set(GameState::Menu);
+        state.set(after_run_state(&pending));
     }
 }
-
-pub fn has_user_input(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    mouse_button_input: Res<ButtonInput<MouseButton>>,
-    touch_input: Res<Touches>,
-) -> bool {
-    keyboard_input.just_pressed(KeyCode::Space)
-        || mouse_button_input.just_pressed(MouseButton::Left)
-        || touch_input.any_just_pressed()
-}
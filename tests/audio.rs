@@ -0,0 +1,31 @@
+use bevy_superjumper::{GameState, Sound, SoundCooldowns, music::MusicTrack};
+
+#[test]
+fn sounds_of_the_same_moment_play_once() {
+    let mut cooldowns = SoundCooldowns::default();
+
+    assert!(cooldowns.try_play(Sound::Coin, 1.0));
+    assert!(!cooldowns.try_play(Sound::Coin, 1.0));
+    assert!(!cooldowns.try_play(Sound::Coin, 1.05));
+    assert!(cooldowns.try_play(Sound::Coin, 1.1));
+}
+
+#[test]
+fn cooldowns_are_per_sound() {
+    let mut cooldowns = SoundCooldowns::default();
+
+    assert!(cooldowns.try_play(Sound::Coin, 1.0));
+    assert!(cooldowns.try_play(Sound::Jump, 1.0));
+    assert!(!cooldowns.try_play(Sound::Jump, 1.0));
+}
+
+#[test]
+fn screens_to_rest_on_get_the_calm_track() {
+    assert_eq!(MusicTrack::for_state(GameState::Playing), MusicTrack::Game);
+    assert_eq!(MusicTrack::for_state(GameState::Menu), MusicTrack::Game);
+    assert_eq!(
+        MusicTrack::for_state(GameState::WinScreen),
+        MusicTrack::Calm
+    );
+    assert_eq!(MusicTrack::for_state(GameState::Help), MusicTrack::Calm);
+}
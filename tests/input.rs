@@ -0,0 +1,60 @@
+use bevy::prelude::{KeyCode, Vec2};
+use bevy_superjumper::input::{Action, Binding, Bindings, TouchSteering};
+
+// The actions the input triggers
+fn actions_of(bindings: &Bindings, binding: Binding) -> Vec<Action> {
+    Action::ALL
+        .into_iter()
+        .filter(|&action| bindings.get(action).contains(&binding))
+        .collect()
+}
+
+#[test]
+fn holding_a_half_of_the_screen_steers_towards_it() {
+    let left = Vec2::new(100.0, 300.0);
+    let right = Vec2::new(300.0, 300.0);
+
+    assert_eq!(TouchSteering::Halves.move_input(left, left, 320.0), -1.0);
+    assert_eq!(TouchSteering::Halves.move_input(right, right, 320.0), 1.0);
+}
+
+#[test]
+fn dragging_steers_relative_to_where_the_finger_went_down() {
+    let start = Vec2::new(300.0, 300.0);
+
+    assert_eq!(TouchSteering::Drag.move_input(start, start, 320.0), 0.0);
+    assert_eq!(
+        TouchSteering::Drag.move_input(start, start + Vec2::new(-20.0, 50.0), 320.0),
+        -0.5
+    );
+    assert_eq!(
+        TouchSteering::Drag.move_input(start, start + Vec2::new(200.0, 0.0), 320.0),
+        1.0
+    );
+}
+
+#[test]
+fn no_default_input_triggers_two_actions() {
+    let bindings = Bindings::default();
+
+    for action in Action::ALL {
+        for &binding in bindings.get(action) {
+            assert_eq!(actions_of(&bindings, binding), [action]);
+        }
+    }
+}
+
+#[test]
+fn rebinding_a_taken_input_swaps_the_bindings() {
+    let mut bindings = Bindings::default();
+    bindings.rebind(Action::Pause, Binding::Key(KeyCode::Escape));
+
+    assert_eq!(
+        actions_of(&bindings, Binding::Key(KeyCode::Escape)),
+        [Action::Pause]
+    );
+    assert_eq!(
+        actions_of(&bindings, Binding::Key(KeyCode::KeyP)),
+        [Action::Back]
+    );
+}
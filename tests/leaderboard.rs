@@ -0,0 +1,59 @@
+use std::env;
+
+use bevy_superjumper::leaderboard::{LeaderboardEntry, LeaderboardStore, STORED_COUNT};
+
+fn entry(name: &str, score: u32) -> LeaderboardEntry {
+    LeaderboardEntry {
+        name: name.to_string(),
+        score,
+        date: Some(1_700_000_000),
+        seed: 42,
+        level: None,
+    }
+}
+
+fn names(entries: &[LeaderboardEntry]) -> Vec<&str> {
+    entries.iter().map(|entry| entry.name.as_str()).collect()
+}
+
+#[test]
+fn best_runs_come_first() {
+    let mut store = LeaderboardStore::default();
+    store.insert(entry("A", 100));
+    store.insert(entry("B", 300));
+    store.insert(entry("C", 200));
+    store.insert(entry("D", 300));
+
+    assert_eq!(names(store.top(3)), ["B", "D", "C"]);
+    assert_eq!(store.top(10).len(), 4);
+}
+
+#[test]
+fn only_the_best_runs_are_stored() {
+    let mut store = LeaderboardStore::default();
+    for score in 0..STORED_COUNT as u32 + 10 {
+        store.insert(entry("A", score));
+    }
+
+    let top = store.top(STORED_COUNT + 10);
+    assert_eq!(top.len(), STORED_COUNT);
+    assert_eq!(top.last().unwrap().score, 10);
+}
+
+#[test]
+fn scores_survive_a_restart() {
+    let path = env::temp_dir().join(format!(
+        "superjumper_leaderboard_{}.ron",
+        std::process::id()
+    ));
+    let mut store = LeaderboardStore::read(&path).unwrap();
+    assert!(store.top(10).is_empty());
+
+    store.insert(entry("A", 100));
+    store.insert(entry("B", 200));
+    store.write(&path).unwrap();
+
+    let read = LeaderboardStore::read(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(read.top(10), [entry("B", 200), entry("A", 100)]);
+}
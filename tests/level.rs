@@ -0,0 +1,103 @@
+use bevy_superjumper::game::{
+    level::{GameObject, GameObjectType, LevelAsset, check_level, generate_level},
+    platform::PLATFORM_VELOCITY_X,
+};
+use rand::{SeedableRng, rngs::StdRng};
+
+fn object(object_type: GameObjectType, x: f32, y: f32) -> GameObject {
+    GameObject {
+        object_type,
+        x,
+        y,
+        is_spawned: false,
+    }
+}
+
+#[test]
+fn platforms_within_a_jump_have_a_path() {
+    let objects = vec![
+        object(GameObjectType::Platform(0.0), 40.0, 120.0),
+        object(GameObjectType::Platform(PLATFORM_VELOCITY_X), 280.0, 260.0),
+        object(GameObjectType::Platform(0.0), 160.0, 400.0),
+        object(GameObjectType::Castle, 160.0, 560.0),
+    ];
+
+    assert!(check_level(&objects).is_ok());
+}
+
+#[test]
+fn a_gap_too_high_has_no_path() {
+    let objects = vec![
+        object(GameObjectType::Platform(0.0), 160.0, 120.0),
+        object(GameObjectType::Platform(0.0), 160.0, 400.0),
+        object(GameObjectType::Castle, 160.0, 560.0),
+    ];
+
+    let no_path = check_level(&objects).unwrap_err();
+    assert_eq!(no_path.stuck_at, 120.0);
+}
+
+#[test]
+fn springs_jump_higher() {
+    let objects = vec![
+        object(GameObjectType::Platform(0.0), 160.0, 120.0),
+        object(GameObjectType::Spring, 160.0, 132.8),
+        object(GameObjectType::Platform(0.0), 160.0, 400.0),
+        object(GameObjectType::Castle, 160.0, 560.0),
+    ];
+
+    assert!(check_level(&objects).is_ok());
+}
+
+// Bob can't wait on the moving platform for the squirrel to leave the only
+// platform the castle can be reached from
+#[test]
+fn a_squirrel_blocking_the_only_jump_leaves_no_path() {
+    let objects = vec![
+        object(GameObjectType::Platform(PLATFORM_VELOCITY_X), 160.0, 140.0),
+        object(GameObjectType::Platform(0.0), 160.0, 280.0),
+        object(GameObjectType::Squirrel, 160.0, 297.6),
+        object(GameObjectType::Castle, 160.0, 440.0),
+    ];
+
+    let no_path = check_level(&objects).unwrap_err();
+    assert_eq!(no_path.stuck_at, 140.0);
+}
+
+#[test]
+fn a_squirrel_off_the_path_leaves_it() {
+    let objects = vec![
+        object(GameObjectType::Platform(PLATFORM_VELOCITY_X), 160.0, 140.0),
+        object(GameObjectType::Platform(0.0), 160.0, 280.0),
+        object(GameObjectType::Squirrel, 40.0, 200.0),
+        object(GameObjectType::Castle, 160.0, 440.0),
+    ];
+
+    assert!(check_level(&objects).is_ok());
+}
+
+#[test]
+fn generated_levels_have_a_path_and_are_reproducible() {
+    for seed in 0..20 {
+        let level = generate_level(&mut StdRng::seed_from_u64(seed));
+        let again = generate_level(&mut StdRng::seed_from_u64(seed));
+
+        assert!(check_level(&level).is_ok());
+        assert!(
+            level
+                .iter()
+                .zip(&again)
+                .all(|(a, b)| a.x == b.x && a.y == b.y)
+        );
+    }
+}
+
+#[test]
+fn level_files_have_a_path() {
+    for entry in std::fs::read_dir("assets/levels").unwrap() {
+        let path = entry.unwrap().path();
+        let level: LevelAsset = ron::de::from_bytes(&std::fs::read(&path).unwrap()).unwrap();
+
+        assert!(check_level(&level.objects).is_ok(), "{}", path.display());
+    }
+}
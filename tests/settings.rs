@@ -0,0 +1,181 @@
+use std::{env, fs, path::PathBuf};
+
+use bevy::prelude::{GamepadButton, KeyCode};
+use bevy_superjumper::{
+    VolumeChannel, Volumes,
+    input::{Action, Binding, Bindings, TouchSteering},
+    settings::{Settings, update_settings_file},
+};
+
+// A settings file of this test process, removed beforehand
+fn settings_path(name: &str) -> PathBuf {
+    let path = env::temp_dir().join(format!(
+        "superjumper_settings_{}_{}.ron",
+        name,
+        std::process::id()
+    ));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+fn scores(settings: &Settings) -> Vec<u32> {
+    settings
+        .high_scores
+        .iter()
+        .map(|entry| entry.score)
+        .collect()
+}
+
+#[test]
+fn legacy_settings_are_migrated() {
+    let settings = Settings::from_legacy("false\n500\n400\n300\n200\n100\n").unwrap();
+
+    assert!(!settings.sound_enabled);
+    assert_eq!(scores(&settings), [500, 400, 300, 200, 100]);
+}
+
+#[test]
+fn invalid_legacy_settings_are_rejected() {
+    assert!(Settings::from_legacy("maybe\n1\n2\n3\n4\n5\n").is_err());
+    assert!(Settings::from_legacy("true\n1\n2\n").is_err());
+}
+
+#[test]
+fn version_1_high_scores_are_migrated() {
+    let settings =
+        Settings::from_ron("(version: 1, sound_enabled: false, high_scores: (5, 4, 3, 2, 1))")
+            .unwrap();
+
+    assert!(!settings.sound_enabled);
+    assert_eq!(scores(&settings), [5, 4, 3, 2, 1]);
+    assert!(
+        settings
+            .high_scores
+            .iter()
+            .all(|entry| entry.date.is_none())
+    );
+}
+
+#[test]
+fn settings_survive_a_round_trip() {
+    let settings = Settings::from_legacy("false\n5\n4\n3\n2\n1\n").unwrap();
+    let read = Settings::from_ron(&settings.to_ron().unwrap()).unwrap();
+
+    assert!(!read.sound_enabled);
+    assert_eq!(scores(&read), [5, 4, 3, 2, 1]);
+    assert_eq!(read.high_scores[0].name, settings.high_scores[0].name);
+}
+
+#[test]
+fn unknown_and_missing_fields_are_tolerated() {
+    let settings =
+        Settings::from_ron("(version: 2, sound_enabled: false, from_the_future: 42)").unwrap();
+
+    assert!(!settings.sound_enabled);
+    assert_eq!(scores(&settings), scores(&Settings::default()));
+}
+
+#[test]
+fn rebound_actions_are_kept() {
+    let mut settings = Settings::default();
+    settings
+        .bindings
+        .rebind(Action::MoveLeft, Binding::Key(KeyCode::KeyJ));
+    let read = Settings::from_ron(&settings.to_ron().unwrap()).unwrap();
+
+    // The new key replaces the other keys, the gamepad binding stays
+    assert_eq!(
+        read.bindings.get(Action::MoveLeft),
+        [
+            Binding::Gamepad(GamepadButton::DPadLeft),
+            Binding::Key(KeyCode::KeyJ)
+        ]
+    );
+    assert_eq!(
+        read.bindings.get(Action::MoveRight),
+        Bindings::default().get(Action::MoveRight)
+    );
+}
+
+#[test]
+fn missing_bindings_are_defaults() {
+    let settings = Settings::from_ron("(version: 2, bindings: (pause: [Key(KeyQ)]))").unwrap();
+
+    assert_eq!(
+        settings.bindings.get(Action::Pause),
+        [Binding::Key(KeyCode::KeyQ)]
+    );
+    assert_eq!(
+        settings.bindings.get(Action::Confirm),
+        Bindings::default().get(Action::Confirm)
+    );
+}
+
+#[test]
+fn touch_steering_is_kept() {
+    let mut settings = Settings::default();
+    settings.touch_steering = TouchSteering::Drag;
+    let read = Settings::from_ron(&settings.to_ron().unwrap()).unwrap();
+
+    assert_eq!(read.touch_steering, TouchSteering::Drag);
+}
+
+#[test]
+fn volumes_are_kept() {
+    let mut settings = Settings::default();
+    settings.volumes.adjust(VolumeChannel::Music, -3);
+    let read = Settings::from_ron(&settings.to_ron().unwrap()).unwrap();
+
+    assert_eq!(read.volumes.get(VolumeChannel::Music), 0.7);
+    assert_eq!(read.volumes.get(VolumeChannel::Master), 1.0);
+}
+
+#[test]
+fn volumes_stay_within_range() {
+    let mut volumes = Volumes::default();
+    volumes.adjust(VolumeChannel::Effects, 2);
+    assert_eq!(volumes.effects, 1.0);
+
+    volumes.adjust(VolumeChannel::Effects, -15);
+    assert_eq!(volumes.effects, 0.0);
+
+    volumes.adjust(VolumeChannel::Effects, 1);
+    assert_eq!(volumes.effects, 0.1);
+}
+
+#[test]
+fn updates_keep_the_other_settings() {
+    let path = settings_path("update");
+    update_settings_file(&path, |settings| settings.sound_enabled = false).unwrap();
+    update_settings_file(&path, |settings| {
+        settings.touch_steering = TouchSteering::Drag
+    })
+    .unwrap();
+
+    let settings = Settings::from_ron(&fs::read_to_string(&path).unwrap()).unwrap();
+    assert!(!settings.sound_enabled);
+    assert_eq!(settings.touch_steering, TouchSteering::Drag);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn unreadable_settings_are_not_overwritten() {
+    let path = settings_path("unreadable");
+    fs::write(&path, "(sound_enabled: fals").unwrap();
+
+    assert!(update_settings_file(&path, |settings| settings.sound_enabled = true).is_err());
+    assert_eq!(fs::read_to_string(&path).unwrap(), "(sound_enabled: fals");
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn newer_settings_are_not_overwritten() {
+    let path = settings_path("newer");
+    let newer = "(version: 99, sound_enabled: false, from_the_future: 42)";
+    fs::write(&path, newer).unwrap();
+
+    assert!(Settings::from_ron(newer).is_err());
+    assert!(update_settings_file(&path, |settings| settings.sound_enabled = true).is_err());
+    assert_eq!(fs::read_to_string(&path).unwrap(), newer);
+    fs::remove_file(&path).unwrap();
+}
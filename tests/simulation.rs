@@ -0,0 +1,346 @@
+use std::time::Duration;
+
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+use bevy_superjumper::{
+    GameState, Sound, SoundEvent,
+    game::{
+        ActiveReplay, GameObjects, LevelOverride, MoveInput, PlayState, Playtest, Points, Practice,
+        Replay, SeedOverride, SimulationPlugin,
+        bob::{ACCELERATION_X, BOB_JUMP_VELOCITY, BOB_MOVE_VELOCITY, Bob},
+        castle::Castle,
+        coin::{COIN_SCORE, Coin},
+        collision::{BobTouched, Collider, CollisionLayer},
+        level::{GameObject, GameObjectType, HandMadeLevel},
+        physics::{Position, TICKS_PER_SECOND},
+        platform::Platform,
+        spring::{SPRING_JUMP_VELOCITY, Spring},
+        squirrel::Squirrel,
+    },
+};
+
+// Builds a headless game, with one simulation tick per update
+fn app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, SimulationPlugin))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+            1.0 / TICKS_PER_SECOND,
+        )))
+        .insert_resource(SeedOverride(Some(1)));
+    app.update();
+    app
+}
+
+fn start_run(app: &mut App) {
+    app.world_mut()
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Playing);
+    app.update();
+    app.world_mut()
+        .resource_mut::<NextState<PlayState>>()
+        .set(PlayState::Running);
+    app.update();
+}
+
+// Starts a run in a level that only contains the given objects
+fn start_run_with(app: &mut App, objects: Vec<(GameObjectType, Vec2)>) {
+    start_run(app);
+
+    let world = app.world_mut();
+    let mut spawned = world.query_filtered::<Entity, Or<(
+        With<Platform>,
+        With<Spring>,
+        With<Coin>,
+        With<Squirrel>,
+        With<Castle>,
+    )>>();
+    for entity in spawned.iter(world).collect::<Vec<_>>() {
+        world.despawn(entity);
+    }
+
+    world.resource_mut::<GameObjects>().0 = objects
+        .into_iter()
+        .map(|(object_type, position)| GameObject {
+            object_type,
+            x: position.x,
+            y: position.y,
+            is_spawned: false,
+        })
+        .collect();
+}
+
+fn bob(app: &mut App) -> (Vec2, Vec2) {
+    let world = app.world_mut();
+    let (position, bob) = world.query::<(&Position, &Bob)>().single(world).unwrap();
+    (position.0, bob.velocity)
+}
+
+fn play_state(app: &App) -> PlayState {
+    *app.world().resource::<State<PlayState>>().get()
+}
+
+fn game_state(app: &App) -> GameState {
+    *app.world().resource::<State<GameState>>().get()
+}
+
+#[test]
+fn landing_on_a_platform_jumps() {
+    let mut app = app();
+    start_run_with(
+        &mut app,
+        vec![(GameObjectType::Platform(0.0), Vec2::new(160.0, 150.0))],
+    );
+
+    // The floor also bounces Bob, so only count jumps well above it
+    let jumped = (0..120).any(|_| {
+        app.update();
+        let (position, velocity) = bob(&mut app);
+        position.y > -150.0 && velocity.y == BOB_JUMP_VELOCITY
+    });
+
+    assert!(jumped);
+}
+
+#[test]
+fn falling_fast_does_not_pass_through_a_platform() {
+    let mut app = app();
+    start_run_with(
+        &mut app,
+        vec![(GameObjectType::Platform(0.0), Vec2::new(160.0, 240.0))],
+    );
+    app.update();
+
+    // Falls 100px in a tick, from above the platform to below it
+    let world = app.world_mut();
+    let (mut position, mut falling_bob) = world
+        .query::<(&mut Position, &mut Bob)>()
+        .single_mut(world)
+        .unwrap();
+    position.0 = Vec2::new(0.0, 40.0);
+    falling_bob.velocity = Vec2::new(0.0, -100.0 * TICKS_PER_SECOND as f32);
+    app.update();
+
+    let (position, velocity) = bob(&mut app);
+    assert_eq!(velocity.y, BOB_JUMP_VELOCITY);
+    assert!(position.y > 20.0);
+}
+
+#[test]
+fn landing_on_a_spring_jumps_higher() {
+    let mut app = app();
+    start_run_with(
+        &mut app,
+        vec![(GameObjectType::Spring, Vec2::new(160.0, 60.0))],
+    );
+
+    let jumped = (0..120).any(|_| {
+        app.update();
+        bob(&mut app).1.y == SPRING_JUMP_VELOCITY
+    });
+
+    assert!(jumped);
+}
+
+#[test]
+fn any_collider_can_be_touched() {
+    let mut app = app();
+    start_run_with(&mut app, vec![]);
+
+    let (position, _) = bob(&mut app);
+    let entity = app
+        .world_mut()
+        .spawn((
+            Position(position),
+            Collider::rect(Vec2::splat(32.0), CollisionLayer::Touch),
+        ))
+        .id();
+
+    let mut cursor = app.world().resource::<Messages<BobTouched>>().get_cursor();
+    app.update();
+    let messages = app.world().resource::<Messages<BobTouched>>();
+    assert!(cursor.read(messages).any(|touched| touched.0 == entity));
+}
+
+#[test]
+fn touching_a_squirrel_is_game_over() {
+    let mut app = app();
+    start_run_with(
+        &mut app,
+        vec![(GameObjectType::Squirrel, Vec2::new(160.0, 32.0))],
+    );
+
+    for _ in 0..10 {
+        app.update();
+    }
+
+    assert_eq!(play_state(&app), PlayState::GameOver);
+}
+
+#[test]
+fn collecting_a_coin_scores() {
+    let mut app = app();
+    start_run_with(
+        &mut app,
+        vec![(GameObjectType::Coin, Vec2::new(160.0, 60.0))],
+    );
+
+    for _ in 0..60 {
+        app.update();
+    }
+
+    assert_eq!(app.world().resource::<Points>().0, COIN_SCORE);
+    let world = app.world_mut();
+    assert_eq!(world.query::<&Coin>().iter(world).count(), 0);
+}
+
+#[test]
+fn coin_sounds_come_from_the_coin() {
+    let mut app = app();
+    start_run_with(
+        &mut app,
+        vec![(GameObjectType::Coin, Vec2::new(160.0, 60.0))],
+    );
+
+    let mut cursor = app.world().resource::<Messages<SoundEvent>>().get_cursor();
+    let mut sounds = Vec::new();
+    for _ in 0..60 {
+        app.update();
+        let messages = app.world().resource::<Messages<SoundEvent>>();
+        sounds.extend(cursor.read(messages).copied());
+    }
+
+    let coin = sounds.iter().find(|event| event.sound == Sound::Coin);
+    assert_eq!(coin.unwrap().position, Some(Vec2::new(0.0, -180.0)));
+}
+
+#[test]
+fn reaching_the_castle_wins() {
+    let mut app = app();
+    start_run_with(
+        &mut app,
+        vec![(GameObjectType::Castle, Vec2::new(160.0, 100.0))],
+    );
+
+    for _ in 0..60 {
+        app.update();
+    }
+
+    assert_eq!(game_state(&app), GameState::WinScreen);
+}
+
+#[test]
+fn moving_right_wraps_around() {
+    let mut app = app();
+    start_run_with(&mut app, vec![]);
+
+    app.world_mut().resource_mut::<MoveInput>().0 = 1.0;
+    let mut previous_x = bob(&mut app).0.x;
+    let mut wrapped = false;
+    for _ in 0..120 {
+        app.update();
+        let x = bob(&mut app).0.x;
+        wrapped |= x < previous_x;
+        previous_x = x;
+    }
+
+    assert!(wrapped);
+}
+
+#[test]
+fn partial_input_moves_in_proportion() {
+    let mut app = app();
+    start_run_with(&mut app, vec![]);
+
+    app.world_mut().resource_mut::<MoveInput>().0 = 0.5;
+    app.update();
+
+    // Inputs are recorded with 8 bits of precision
+    let full_speed = ACCELERATION_X * BOB_MOVE_VELOCITY;
+    assert!((bob(&mut app).1.x - 0.5 * full_speed).abs() < 0.01 * full_speed);
+}
+
+#[test]
+fn same_seed_and_input_give_the_same_run() {
+    let run = || {
+        let mut app = app();
+        start_run(&mut app);
+
+        (0..300)
+            .map(|tick| {
+                app.world_mut().resource_mut::<MoveInput>().0 =
+                    if tick % 90 < 45 { 1.0 } else { -0.5 };
+                app.update();
+                bob(&mut app)
+            })
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(run(), run());
+}
+
+// A level with nothing but a castle, right above the start
+fn castle_level() -> HandMadeLevel {
+    HandMadeLevel {
+        name: "castle".to_string(),
+        objects: vec![GameObject {
+            object_type: GameObjectType::Castle,
+            x: 160.0,
+            y: 100.0,
+            is_spawned: false,
+        }],
+    }
+}
+
+#[test]
+fn level_override_replaces_the_generated_level() {
+    let mut app = app();
+    app.insert_resource(LevelOverride(Some(castle_level())));
+    start_run(&mut app);
+
+    for _ in 0..60 {
+        app.update();
+    }
+
+    assert_eq!(game_state(&app), GameState::WinScreen);
+}
+
+#[test]
+fn replays_are_played_on_their_own_level() {
+    let mut app = app();
+    app.insert_resource(LevelOverride(Some(castle_level())));
+    app.world_mut()
+        .resource_mut::<ActiveReplay>()
+        .start(Replay {
+            seed: 1,
+            inputs: vec![0; 60],
+            ..default()
+        });
+    start_run(&mut app);
+
+    for _ in 0..60 {
+        app.update();
+    }
+
+    assert_eq!(game_state(&app), GameState::Playing);
+}
+
+#[test]
+fn playtest_drops_bob_at_the_given_height() {
+    let mut app = app();
+    app.insert_resource(Playtest {
+        objects: vec![GameObject {
+            object_type: GameObjectType::Platform(0.0),
+            x: 160.0,
+            y: 1000.0,
+            is_spawned: false,
+        }],
+        height: 800.0,
+    });
+    start_run(&mut app);
+
+    let (position, _) = bob(&mut app);
+    assert!(position.y > 700.0);
+    assert!(!app.world().contains_resource::<Playtest>());
+    assert!(app.world().resource::<Practice>().0);
+    let world = app.world_mut();
+    assert_eq!(world.query::<&Platform>().iter(world).count(), 1);
+}
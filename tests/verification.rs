@@ -0,0 +1,161 @@
+use std::time::Duration;
+
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+use bevy_superjumper::{
+    GameState,
+    game::{
+        FinishedReplay, LevelOverride, MoveInput, PlayState, Points, Replay, RunOutcome,
+        SeedOverride, SimulationPlugin,
+        level::{GameObject, GameObjectType, HandMadeLevel},
+        physics::TICKS_PER_SECOND,
+        verification::{simulate_replay, verify_replay},
+    },
+};
+
+// Steers right and left in turns, for a run that goes somewhere
+fn inputs(ticks: usize) -> Vec<i8> {
+    (0..ticks)
+        .map(|tick| if tick % 90 < 45 { 127 } else { -64 })
+        .collect()
+}
+
+fn replay(score: u32, outcome: Option<RunOutcome>, inputs: Vec<i8>) -> Replay {
+    Replay {
+        seed: 1,
+        level: None,
+        score,
+        outcome,
+        inputs,
+    }
+}
+
+#[test]
+fn replays_keep_their_hand_made_level() {
+    let mut replay = replay(0, None, vec![0; 60]);
+    replay.level = Some(HandMadeLevel {
+        name: "castle".to_string(),
+        objects: vec![GameObject {
+            object_type: GameObjectType::Castle,
+            x: 160.0,
+            y: 100.0,
+            is_spawned: false,
+        }],
+    });
+    let read = Replay::from_bytes(&replay.to_bytes()).unwrap();
+
+    assert_eq!(read.level.as_ref().unwrap().name, "castle");
+    assert_eq!(
+        simulate_replay(&read).outcome,
+        Some(RunOutcome::ReachedCastle)
+    );
+}
+
+#[test]
+fn replays_keep_the_outcome() {
+    let replay = replay(120, Some(RunOutcome::Died), inputs(200));
+    let read = Replay::from_bytes(&replay.to_bytes()).unwrap();
+
+    assert_eq!(read.score, 120);
+    assert_eq!(read.outcome, Some(RunOutcome::Died));
+    assert_eq!(read.inputs, replay.inputs);
+}
+
+#[test]
+fn honest_runs_are_accepted() {
+    // The player quits after 5 seconds, and falls well before 50
+    for (ticks, outcome) in [(300, None), (3000, Some(RunOutcome::Died))] {
+        let result = simulate_replay(&replay(0, None, inputs(ticks)));
+        assert_eq!(result.outcome, outcome);
+
+        let claim = replay(result.points, result.outcome, inputs(ticks));
+        assert_eq!(verify_replay(&claim).unwrap(), result);
+    }
+}
+
+// Starts a run played through the game's input, like the player plays it
+fn recording_app(level: Option<HandMadeLevel>) -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, SimulationPlugin))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+            1.0 / TICKS_PER_SECOND,
+        )))
+        .insert_resource(SeedOverride(Some(7)))
+        .insert_resource(LevelOverride(level));
+    app.update();
+    app.world_mut()
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Playing);
+    app.update();
+    app.world_mut()
+        .resource_mut::<NextState<PlayState>>()
+        .set(PlayState::Running);
+    app
+}
+
+// Steers like the player would, with input the replay can't store exactly,
+// until the run is over
+fn record_run(app: &mut App, right: f32, left: f32) -> Replay {
+    for tick in 0..3000 {
+        app.world_mut().resource_mut::<MoveInput>().0 = if tick % 90 < 45 { right } else { left };
+        app.update();
+        if let Some(replay) = &app.world().resource::<FinishedReplay>().0 {
+            return replay.clone();
+        }
+    }
+    panic!("the run didn't finish");
+}
+
+#[test]
+fn recorded_runs_are_accepted() {
+    let mut app = recording_app(None);
+    let recorded = record_run(&mut app, 0.73, -0.41);
+
+    assert_eq!(recorded.score, app.world().resource::<Points>().0);
+    let read = Replay::from_bytes(&recorded.to_bytes()).unwrap();
+    let result = verify_replay(&read).unwrap();
+    assert_eq!(result.points, recorded.score);
+    assert_eq!(result.outcome, Some(RunOutcome::Died));
+}
+
+#[test]
+fn castle_runs_are_recorded_and_accepted() {
+    let castle = HandMadeLevel {
+        name: "castle".to_string(),
+        objects: vec![GameObject {
+            object_type: GameObjectType::Castle,
+            x: 160.0,
+            y: 100.0,
+            is_spawned: false,
+        }],
+    };
+    let mut app = recording_app(Some(castle));
+    let recorded = record_run(&mut app, 0.23, -0.19);
+
+    // Reaching the castle leaves the game, so the replay outlives the run
+    assert_eq!(
+        *app.world().resource::<State<GameState>>(),
+        GameState::WinScreen
+    );
+    assert_eq!(recorded.outcome, Some(RunOutcome::ReachedCastle));
+    let read = Replay::from_bytes(&recorded.to_bytes()).unwrap();
+    let result = verify_replay(&read).unwrap();
+    assert_eq!(result.points, recorded.score);
+    assert_eq!(result.outcome, Some(RunOutcome::ReachedCastle));
+}
+
+#[test]
+fn inflated_scores_are_rejected() {
+    let result = simulate_replay(&replay(0, None, inputs(600)));
+    let claim = replay(result.points + 10, result.outcome, inputs(600));
+
+    let rejected = verify_replay(&claim).unwrap_err();
+    assert_eq!(rejected.simulated, result);
+}
+
+#[test]
+fn claimed_castles_are_rejected() {
+    let result = simulate_replay(&replay(0, None, inputs(600)));
+    let claim = replay(result.points, Some(RunOutcome::ReachedCastle), inputs(600));
+
+    assert!(verify_replay(&claim).is_err());
+}